@@ -0,0 +1,203 @@
+//! Raise an alert when a device's battery drops below a threshold.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::config::{BatteryConfig, Percent};
+
+use super::BTDevice;
+
+/// A tripped threshold is only re-armed once the battery climbs this far back above it,
+/// so a level wobbling around the threshold doesn't alert over and over.
+const HYSTERESIS: u8 = 5;
+
+#[derive(Debug, Clone, Copy)]
+pub struct BatteryAlert {
+    pub level: u8,
+    pub threshold: u8,
+    /// The device has crossed its lowest threshold.
+    pub critical: bool,
+}
+
+impl BatteryAlert {
+    /// The alert for the device at `address` as one line of JSON, for `--events`.
+    pub fn to_json(self, address: &str, name: &str) -> String {
+        #[derive(Serialize)]
+        struct Event<'a> {
+            event: &'static str,
+            address: &'a str,
+            name: &'a str,
+            level: u8,
+            threshold: u8,
+            critical: bool,
+        }
+
+        let event = Event {
+            event: "low-battery",
+            address,
+            name,
+            level: self.level,
+            threshold: self.threshold,
+            critical: self.critical,
+        };
+
+        serde_json::to_string(&event).expect("event is always valid JSON")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BatteryAlerts {
+    global: Vec<u8>,
+    per_device: HashMap<String, Vec<u8>>,
+    /// Thresholds each device is currently below.
+    tripped: HashMap<String, HashSet<u8>>,
+}
+
+impl BatteryAlerts {
+    pub fn new(config: &BatteryConfig) -> Self {
+        let levels = |l: &[Percent]| l.iter().map(|p| p.0).collect::<Vec<u8>>();
+
+        BatteryAlerts {
+            global: levels(&config.low),
+            per_device: config
+                .devices
+                .iter()
+                .map(|(address, l)| (address.0.to_string().to_lowercase(), levels(l)))
+                .collect(),
+            tripped: HashMap::new(),
+        }
+    }
+
+    /// Check `device` against its thresholds, returning an alert for the lowest one it has
+    /// newly dropped to or below.
+    pub fn check(&mut self, device: &BTDevice) -> Option<BatteryAlert> {
+        self.check_level(&device.address, device.battery_level()?)
+    }
+
+    fn check_level(&mut self, address: &str, level: u8) -> Option<BatteryAlert> {
+        let thresholds = self.per_device.get(address).unwrap_or(&self.global);
+        let tripped = self.tripped.entry(address.to_string()).or_default();
+
+        tripped.retain(|t| level < t.saturating_add(HYSTERESIS));
+
+        let crossed = thresholds
+            .iter()
+            .filter(|t| level <= **t && !tripped.contains(t))
+            .copied()
+            .collect::<Vec<u8>>();
+
+        tripped.extend(&crossed);
+
+        let threshold = crossed.into_iter().min()?;
+
+        Some(BatteryAlert {
+            level,
+            threshold,
+            critical: thresholds.iter().all(|t| *t >= threshold),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::DeviceAddress;
+
+    use super::*;
+
+    const ADDRESS: &str = "aa:bb:cc:dd:ee:ff";
+
+    fn alerts(low: &[u8]) -> BatteryAlerts {
+        BatteryAlerts::new(&BatteryConfig {
+            low: low.iter().map(|l| Percent(*l)).collect(),
+            devices: HashMap::new(),
+        })
+    }
+
+    /// The threshold alerted at for each level in turn, if any.
+    fn thresholds(alerts: &mut BatteryAlerts, levels: &[u8]) -> Vec<Option<u8>> {
+        levels
+            .iter()
+            .map(|l| alerts.check_level(ADDRESS, *l).map(|a| a.threshold))
+            .collect()
+    }
+
+    #[test]
+    fn alert_as_json() {
+        let alert = BatteryAlert {
+            level: 9,
+            threshold: 10,
+            critical: true,
+        };
+
+        assert_eq!(
+            alert.to_json("aa:bb:cc:dd:ee:ff", "Jo's \"Buds\"\\\n\t"),
+            r#"{"event":"low-battery","address":"aa:bb:cc:dd:ee:ff","name":"Jo's \"Buds\"\\\n\t","level":9,"threshold":10,"critical":true}"#
+        );
+    }
+
+    #[test]
+    fn crossing_thresholds() {
+        let mut alerts = alerts(&[20, 10]);
+
+        assert_eq!(
+            thresholds(&mut alerts, &[50, 21, 20, 15, 10, 5]),
+            [None, None, Some(20), None, Some(10), None]
+        );
+
+        let first = alerts.check_level("11:22:33:44:55:66", 18).unwrap();
+        assert_eq!((first.level, first.threshold), (18, 20));
+        assert!(!first.critical);
+
+        let last = alerts.check_level("11:22:33:44:55:66", 9).unwrap();
+        assert_eq!((last.level, last.threshold), (9, 10));
+        assert!(last.critical);
+    }
+
+    #[test]
+    fn lowest_threshold_crossed_at_once() {
+        let mut alerts = alerts(&[20, 10]);
+
+        let alert = alerts.check_level(ADDRESS, 8).unwrap();
+        assert_eq!(alert.threshold, 10);
+        assert!(alert.critical);
+
+        // Both are tripped, so neither alerts again
+        assert_eq!(thresholds(&mut alerts, &[9, 12, 18]), [None, None, None]);
+    }
+
+    #[test]
+    fn hovering_around_threshold() {
+        let mut alerts = alerts(&[20]);
+
+        assert_eq!(
+            thresholds(&mut alerts, &[20, 21, 19, 22, 20, 24, 18]),
+            [Some(20), None, None, None, None, None, None]
+        );
+    }
+
+    #[test]
+    fn rearm_after_charging() {
+        let mut alerts = alerts(&[20]);
+
+        assert_eq!(
+            thresholds(&mut alerts, &[20, 25, 30, 20]),
+            [Some(20), None, None, Some(20)]
+        );
+    }
+
+    #[test]
+    fn per_device_thresholds() {
+        let mut config = BatteryConfig {
+            low: vec![Percent(20)],
+            devices: HashMap::new(),
+        };
+        config
+            .devices
+            .insert(DeviceAddress(ADDRESS.parse().unwrap()), vec![Percent(50)]);
+        let mut alerts = BatteryAlerts::new(&config);
+
+        assert_eq!(thresholds(&mut alerts, &[50, 20]), [Some(50), None]);
+        assert!(alerts.check_level("11:22:33:44:55:66", 50).is_none());
+    }
+}
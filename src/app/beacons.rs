@@ -0,0 +1,198 @@
+//! Track iBeacon and Eddystone-UID beacons seen in device advertisements.
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use bluer::Uuid;
+
+use crate::decode::{sig_uuid, EddystoneUid, IBeacon, APPLE_COMPANY_ID};
+
+use super::{
+    history::{Ema, RssiReadings, RSSI_SMOOTHING},
+    BTDevice,
+};
+
+const EDDYSTONE_SERVICE: u16 = 0xFEAA;
+
+/// Path loss exponent, 2.0 in free space and higher indoors.
+const PATH_LOSS_EXPONENT: f64 = 2.0;
+
+/// Eddystone calibrates tx power at 0m, iBeacon at 1m. The difference is the free space
+/// loss over that first meter.
+const EDDYSTONE_1M_LOSS: i16 = 41;
+
+/// Beacons not heard from for this long are forgotten.
+const BEACON_TIMEOUT: Duration = Duration::from_secs(2 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BeaconId {
+    IBeacon {
+        uuid: Uuid,
+        major: u16,
+        minor: u16,
+    },
+    Eddystone {
+        namespace: [u8; 10],
+        instance: [u8; 6],
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Beacon {
+    pub id: BeaconId,
+    pub address: String,
+    /// Calibrated RSSI at 1m.
+    pub tx_power: i16,
+    pub rssi: Ema,
+    pub last_seen: Instant,
+    readings: RssiReadings,
+}
+
+impl Beacon {
+    /// Estimated distance in meters, using the log-distance path loss model.
+    pub fn distance(&self) -> Option<f64> {
+        self.rssi
+            .value()
+            .map(|rssi| 10f64.powf((self.tx_power as f64 - rssi) / (10.0 * PATH_LOSS_EXPONENT)))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct BeaconTracker {
+    beacons: HashMap<BeaconId, Beacon>,
+}
+
+impl BeaconTracker {
+    /// Record any beacon frames advertised by `device`.
+    pub fn update(&mut self, device: &BTDevice) {
+        let ibeacon = device
+            .manufacturer_data
+            .get(&APPLE_COMPANY_ID)
+            .and_then(|data| IBeacon::from_manufacturer_data(data))
+            .map(|b| {
+                (
+                    BeaconId::IBeacon {
+                        uuid: b.uuid,
+                        major: b.major,
+                        minor: b.minor,
+                    },
+                    b.tx_power as i16,
+                )
+            });
+
+        let eddystone = device
+            .service_data
+            .get(&sig_uuid(EDDYSTONE_SERVICE))
+            .and_then(|data| EddystoneUid::from_service_data(data))
+            .map(|b| {
+                (
+                    BeaconId::Eddystone {
+                        namespace: b.namespace,
+                        instance: b.instance,
+                    },
+                    b.tx_power as i16 - EDDYSTONE_1M_LOSS,
+                )
+            });
+
+        let now = Instant::now();
+        self.expire(now);
+
+        for (id, tx_power) in ibeacon.into_iter().chain(eddystone) {
+            let beacon = self.beacons.entry(id).or_insert_with(|| Beacon {
+                id,
+                address: device.address.clone(),
+                tx_power,
+                rssi: Ema::new(RSSI_SMOOTHING),
+                last_seen: now,
+                readings: RssiReadings::default(),
+            });
+
+            beacon.address = device.address.clone();
+            beacon.tx_power = tx_power;
+            beacon.last_seen = now;
+
+            if let Some(rssi) = beacon.readings.fresh(device.rssi) {
+                beacon.rssi.update(rssi as f64);
+            }
+        }
+    }
+
+    /// Forget beacons that haven't been seen within the timeout, as of `now`.
+    pub fn expire(&mut self, now: Instant) {
+        self.beacons
+            .retain(|_, b| now.duration_since(b.last_seen) < BEACON_TIMEOUT);
+    }
+
+    /// Beacons seen recently, nearest first.
+    pub fn beacons(&self) -> Vec<&Beacon> {
+        let mut beacons = self.beacons.values().collect::<Vec<&Beacon>>();
+
+        beacons.sort_by(|a, b| {
+            a.distance()
+                .unwrap_or(f64::MAX)
+                .total_cmp(&b.distance().unwrap_or(f64::MAX))
+                .then(a.id.cmp(&b.id))
+        });
+
+        beacons
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn beacon(minor: u16, last_seen: Instant) -> (BeaconId, Beacon) {
+        let id = BeaconId::IBeacon {
+            uuid: Uuid::nil(),
+            major: 1,
+            minor,
+        };
+
+        let beacon = Beacon {
+            id,
+            address: "aa:bb:cc:dd:ee:ff".to_string(),
+            tx_power: -59,
+            rssi: Ema::new(RSSI_SMOOTHING),
+            last_seen,
+            readings: RssiReadings::default(),
+        };
+
+        (id, beacon)
+    }
+
+    #[test]
+    fn expire_stale_beacons() {
+        let start = Instant::now();
+        let mut tracker = BeaconTracker {
+            beacons: [beacon(1, start), beacon(2, start + Duration::from_secs(60))]
+                .into_iter()
+                .collect(),
+        };
+
+        tracker.expire(start + Duration::from_secs(90));
+        assert_eq!(tracker.beacons().len(), 2);
+
+        tracker.expire(start + BEACON_TIMEOUT);
+        let ids = tracker.beacons().iter().map(|b| b.id).collect::<Vec<_>>();
+        assert_eq!(ids, [beacon(2, start).0]);
+
+        tracker.expire(start + BEACON_TIMEOUT + Duration::from_secs(60));
+        assert!(tracker.beacons().is_empty());
+    }
+
+    #[test]
+    fn distance() {
+        let (_, mut beacon) = beacon(1, Instant::now());
+        assert_eq!(beacon.distance(), None);
+
+        beacon.rssi.update(-59.0);
+        assert!((beacon.distance().unwrap() - 1.0).abs() < 1e-9);
+
+        beacon.rssi = Ema::new(1.0);
+        beacon.rssi.update(-79.0);
+        assert!((beacon.distance().unwrap() - 10.0).abs() < 1e-9);
+    }
+}
@@ -1,19 +1,52 @@
-use std::{cell::RefCell, rc::Rc, sync::Arc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::{stdout, Write},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
 
 use anyhow::Result;
-use ratatui::widgets::TableState;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use log::{debug, info, warn};
+use ratatui::{layout::Position, widgets::TableState};
 use scopeguard::defer;
 use tokio::{
     sync::mpsc::{channel, Receiver, Sender},
+    task::JoinHandle,
     time::{sleep, Duration},
 };
 
 use crate::{
-    app::{bluetooth::launch_bluetooth_listener, input::launch_key_listener},
-    display::{draw_ui, format_inspect_text, init_ui, shutdown_ui, UIState},
+    app::{
+        bluetooth::launch_bluetooth_listener, input::launch_key_listener, ticker::launch_ticker,
+    },
+    config::Config,
+    display::{
+        draw_ui, format_inspect_fields, init_ui, shutdown_ui, DetailsPane, HitAreas, InspectView,
+        UIState,
+    },
+    logging::{log_path, write_event},
+};
+
+use super::{
+    alerts::BatteryAlerts,
+    beacons::BeaconTracker,
+    bluetooth::BTDevice,
+    clipboard::copy_to_clipboard,
+    command::{find_device, Command, CommandLine},
+    eventlog::{EventLog, LogView, Severity},
+    finder::Finder,
+    history::DeviceHistory,
+    notify::{Notifier, NotifyEvent, Urgency},
 };
 
-use super::bluetooth::BTDevice;
+/// Longest gap between two clicks on a device for them to count as a double click.
+const DOUBLE_CLICK: Duration = Duration::from_millis(500);
 
 pub enum AppEvent {
     Pass,
@@ -31,6 +64,25 @@ pub enum AppEvent {
     RemoveRequested,
     ShowHideUnnamed,
     InspectCurrent,
+    PageDown,
+    PageUp,
+    CopyField,
+    CycleDetails,
+    ShowBeacons,
+    FindCurrent,
+    ToggleBell,
+    ShowLog,
+    CycleLogDevice,
+    CycleLogSeverity,
+    SaveLog,
+    ShowLogPath,
+    ShowHelp,
+    ShowCommand,
+    /// A key typed on the command line.
+    CommandKey(KeyEvent),
+    /// A left click at a column and row.
+    Click(u16, u16),
+    Tick,
 }
 
 #[derive(Clone)]
@@ -46,6 +98,9 @@ pub struct Banner(pub String, pub BannerType);
 #[derive(Debug, Clone)]
 pub enum AppMode {
     Browse,
+    Beacons,
+    Find,
+    Log,
     Inspect(BTDevice),
     TryConnect(BTDevice),
     TryPair(BTDevice),
@@ -55,20 +110,40 @@ pub enum AppMode {
 
 pub struct BluetoolApp {
     devices: Rc<RefCell<Vec<BTDevice>>>,
+    beacons: Rc<RefCell<BeaconTracker>>,
+    histories: Rc<RefCell<HashMap<String, DeviceHistory>>>,
+    event_log: Rc<RefCell<EventLog>>,
+    config: Config,
+    battery_alerts: BatteryAlerts,
+    notifier: Option<Notifier>,
     event_recv_chan: Receiver<AppEvent>,
     event_send_chan: Arc<Sender<AppEvent>>,
+    bluetooth_listener: Option<JoinHandle<Result<()>>>,
+    /// Set while the command line is open, so keys are typed instead of looked up.
+    typing: Arc<AtomicBool>,
     mode: AppMode,
     banner: Option<Banner>,
 }
 
 impl BluetoolApp {
     /// Instantiate an instance of the app object
-    pub fn new() -> Self {
+    pub fn new(config: Config) -> Self {
         let (send, recv) = channel(128);
         BluetoolApp {
             devices: Rc::new(RefCell::new(Vec::with_capacity(64))),
+            beacons: Rc::new(RefCell::new(BeaconTracker::default())),
+            histories: Rc::new(RefCell::new(HashMap::new())),
+            event_log: Rc::new(RefCell::new(EventLog::default())),
+            battery_alerts: BatteryAlerts::new(&config.battery),
+            // Notifications are a nicety, carry on without them if there's no session bus
+            notifier: Notifier::connect(&config.notify)
+                .inspect_err(|e| warn!("Desktop notifications unavailable: {}", e))
+                .ok(),
+            config,
             event_recv_chan: recv,
             event_send_chan: Arc::new(send),
+            bluetooth_listener: None,
+            typing: Arc::new(AtomicBool::new(false)),
             mode: AppMode::Browse,
             banner: None,
         }
@@ -80,310 +155,776 @@ impl BluetoolApp {
     }
 
     pub async fn run(&mut self) -> Result<()> {
-        let mut terminal = init_ui()?;
+        let mut terminal = init_ui(self.config.mouse)?;
         let mut ui_state = UIState {
             devices: self.devices.clone(),
+            beacons: self.beacons.clone(),
+            histories: self.histories.clone(),
+            event_log: self.event_log.clone(),
+            log_view: LogView::default(),
+            beacon_scroll: 0,
             table_state: TableState::new(),
             banner: None,
-            show_unnamed: false,
-            inspect_text: None,
+            show_unnamed: self.config.show_unnamed,
+            columns: self.config.columns.clone(),
+            keymap: self.config.keymap.clone(),
+            inspect: None,
+            details: self.config.details,
+            details_fields: None,
+            finder: None,
+            help: None,
+            command: None,
+            filters: vec![],
+            hit_areas: HitAreas::default(),
+            last_click: None,
         };
 
         defer! {
             shutdown_ui().unwrap();
         }
 
-        let key_listener = launch_key_listener(self.get_event_chan_handle());
-        let bluetooth_listener = launch_bluetooth_listener(self.get_event_chan_handle()).await;
+        let key_listener = launch_key_listener(
+            self.get_event_chan_handle(),
+            self.config.keymap.clone(),
+            self.typing.clone(),
+        );
+        self.bluetooth_listener =
+            Some(launch_bluetooth_listener(self.get_event_chan_handle(), &self.config).await);
+        let ticker = launch_ticker(self.get_event_chan_handle());
+
+        debug!("Listeners started");
 
         // NOTE: Send one dummy event so we trigger a draw
         self.event_send_chan.send(AppEvent::Pass).await?;
 
         // Main loop, listen for events and draw ui
-        loop {
-            if let Some(e) = self.event_recv_chan.recv().await {
-                // Process mode-independent events
-                match &e {
-                    AppEvent::Exit => break,
-                    AppEvent::Esc => {
-                        self.mode = AppMode::Browse;
-                        ui_state.inspect_text = None;
+        while let Some(e) = self.event_recv_chan.recv().await {
+            // The help overlay takes every key until it's closed
+            let e = match (ui_state.help, e) {
+                (None, e) => e,
+                (Some(_), AppEvent::ShowHelp | AppEvent::Esc) => {
+                    ui_state.help = None;
+                    AppEvent::Pass
+                }
+                (Some(scroll), AppEvent::ScrollDown) => {
+                    ui_state.help = Some(scroll + 1);
+                    AppEvent::Pass
+                }
+                (Some(scroll), AppEvent::ScrollUp) => {
+                    ui_state.help = Some(scroll.saturating_sub(1));
+                    AppEvent::Pass
+                }
+                (
+                    Some(_),
+                    e @ (AppEvent::Exit
+                    | AppEvent::ShowCommand
+                    | AppEvent::DeviceAdded(_)
+                    | AppEvent::DeviceRemoved(_)
+                    | AppEvent::DeviceModified(_)
+                    | AppEvent::BannerExpired(_)
+                    | AppEvent::Tick),
+                ) => e,
+                (Some(_), _) => AppEvent::Pass,
+            };
+
+            match &e {
+                AppEvent::DeviceAdded(device)
+                    if !self.devices.as_ref().borrow().contains(device) =>
+                {
+                    self.log(Severity::Info, Some(device), "Discovered".to_string());
+                }
+                AppEvent::DeviceRemoved(device) => {
+                    self.log(Severity::Info, Some(device), "Removed".to_string());
+                }
+                AppEvent::DeviceModified(device) => {
+                    if let Some(previous) = self
+                        .devices
+                        .as_ref()
+                        .borrow()
+                        .iter()
+                        .find(|d| d.address == device.address)
+                    {
+                        self.event_log
+                            .as_ref()
+                            .borrow_mut()
+                            .push_changes(previous, device);
                     }
-                    AppEvent::BannerExpired(msg) => {
-                        if let Some(current_banner) = &mut self.banner {
-                            if &current_banner.0 == msg {
-                                self.banner = None;
-                            }
+
+                    self.notify_transitions(device);
+                }
+                _ => {}
+            }
+
+            // Process mode-independent events
+            match &e {
+                AppEvent::Exit => break,
+                AppEvent::Esc => {
+                    self.mode = AppMode::Browse;
+                    ui_state.inspect = None;
+                    ui_state.finder = None;
+                }
+                AppEvent::ShowHelp => ui_state.help = Some(0),
+                AppEvent::ShowCommand => {
+                    ui_state.help = None;
+                    ui_state.command = Some(CommandLine::default());
+                }
+                AppEvent::CommandKey(key) => self.command_key(&mut ui_state, *key).await?,
+                AppEvent::Click(x, y) => self.click(&mut ui_state, *x, *y).await?,
+                AppEvent::Tick => self.beacons.as_ref().borrow_mut().expire(Instant::now()),
+                AppEvent::ShowLogPath => {
+                    let msg = match log_path() {
+                        Some(path) => format!("Logging to {}", path.display()),
+                        None => "Not logging to a file".to_string(),
+                    };
+                    self.set_new_banner(Banner(msg, BannerType::Status), None)
+                        .await;
+                }
+                AppEvent::BannerExpired(msg) => {
+                    if let Some(current_banner) = &mut self.banner {
+                        if &current_banner.0 == msg {
+                            self.banner = None;
                         }
                     }
-                    _ => {}
-                };
+                }
+                AppEvent::DeviceAdded(device) => {
+                    let mut devices = self.devices.as_ref().borrow_mut();
 
-                match self.mode.clone() {
-                    AppMode::Browse => match e {
-                        AppEvent::Exit => break,
-                        AppEvent::ScrollUp => match ui_state.table_state.selected() {
-                            Some(1) => *ui_state.table_state.selected_mut() = None,
-                            Some(idx) => *ui_state.table_state.selected_mut() = Some(idx - 1),
-                            _ => {}
-                        },
-                        AppEvent::ScrollDown => match ui_state.table_state.selected() {
-                            None => {
-                                if self.devices.as_ref().borrow().len() != 0 {
-                                    *ui_state.table_state.selected_mut() = Some(1);
-                                }
-                            }
-                            Some(idx) => *ui_state.table_state.selected_mut() = Some(idx + 1),
-                        },
-                        AppEvent::DeviceAdded(device) => {
-                            let mut devices = self.devices.as_ref().borrow_mut();
+                    if !devices.contains(device) {
+                        devices.push(device.clone());
+                    }
 
-                            if !devices.contains(&device) {
-                                devices.push(device);
-                            }
+                    devices.sort_by(|a, b| self.config.sort.compare(a, b));
 
-                            devices.sort_by(|a, b| b.cmp(a));
-                        }
-                        AppEvent::DeviceRemoved(device) => {
-                            let mut devices = self.devices.as_ref().borrow_mut();
-                            devices.retain(|d| d != &device);
-                        }
-                        AppEvent::DeviceModified(device) => {
-                            let mut devices = self.devices.as_ref().borrow_mut();
-                            let device_mac = device.address.clone();
+                    self.beacons.as_ref().borrow_mut().update(device);
+                    self.histories
+                        .as_ref()
+                        .borrow_mut()
+                        .entry(device.address.clone())
+                        .or_default()
+                        .update(device, Instant::now());
 
-                            for d in devices.iter_mut() {
-                                if d.address == device_mac {
-                                    *d = device.clone();
-                                }
-                            }
+                    if let Some(finder) = &mut ui_state.finder {
+                        finder.update(device);
+                    }
+                }
+                AppEvent::DeviceRemoved(device) => {
+                    let mut devices = self.devices.as_ref().borrow_mut();
+                    devices.retain(|d| d != device);
+                }
+                AppEvent::DeviceModified(device) => {
+                    let mut devices = self.devices.as_ref().borrow_mut();
 
-                            devices.sort_by(|a, b| b.cmp(a));
+                    for d in devices.iter_mut() {
+                        if d.address == device.address {
+                            *d = device.clone();
                         }
-                        AppEvent::ConnectRequested => {
-                            // Find which device we're highlighting
-                            if let Some(idx) = ui_state.table_state.selected() {
-                                let device = self.devices.as_ref().borrow()[idx - 1].clone();
+                    }
 
-                                self.mode = AppMode::TryConnect(device);
-                            }
-                        }
-                        AppEvent::PairRequested => {
-                            // Find which device we're highlighting
-                            if let Some(idx) = ui_state.table_state.selected() {
-                                let device = self.devices.as_ref().borrow()[idx - 1].clone();
+                    devices.sort_by(|a, b| self.config.sort.compare(a, b));
 
-                                self.mode = AppMode::TryPair(device);
-                            }
-                        }
-                        AppEvent::DisconnectRequested => {
-                            // Find which device we're highlighting
-                            if let Some(idx) = ui_state.table_state.selected() {
-                                let device = self.devices.as_ref().borrow()[idx - 1].clone();
+                    self.beacons.as_ref().borrow_mut().update(device);
+                    self.histories
+                        .as_ref()
+                        .borrow_mut()
+                        .entry(device.address.clone())
+                        .or_default()
+                        .update(device, Instant::now());
 
-                                self.mode = AppMode::TryDisconnect(device);
-                            }
-                        }
-                        AppEvent::RemoveRequested => {
-                            // Find which device we're highlighting
-                            if let Some(idx) = ui_state.table_state.selected() {
-                                let device = self.devices.as_ref().borrow()[idx - 1].clone();
+                    if let Some(finder) = &mut ui_state.finder {
+                        finder.update(device);
+                    }
+                }
+                _ => {}
+            };
+
+            if let AppEvent::DeviceAdded(device) | AppEvent::DeviceModified(device) = &e {
+                self.check_battery(device).await;
+            }
 
-                                self.mode = AppMode::TryRemove(device);
+            match self.mode.clone() {
+                AppMode::Browse => match e {
+                    AppEvent::Exit => break,
+                    AppEvent::ScrollUp => match ui_state.table_state.selected() {
+                        Some(1) => *ui_state.table_state.selected_mut() = None,
+                        Some(idx) => *ui_state.table_state.selected_mut() = Some(idx - 1),
+                        _ => {}
+                    },
+                    AppEvent::ScrollDown => match ui_state.table_state.selected() {
+                        None => {
+                            if !self.devices.as_ref().borrow().is_empty() {
+                                *ui_state.table_state.selected_mut() = Some(1);
                             }
                         }
-                        AppEvent::ShowHideUnnamed => {
-                            ui_state.show_unnamed = !ui_state.show_unnamed;
+                        Some(idx) => *ui_state.table_state.selected_mut() = Some(idx + 1),
+                    },
+                    AppEvent::ConnectRequested => {
+                        if let Some(device) = ui_state.selected_device() {
+                            self.mode = AppMode::TryConnect(device);
+                        }
+                    }
+                    AppEvent::PairRequested => {
+                        if let Some(device) = ui_state.selected_device() {
+                            self.mode = AppMode::TryPair(device);
+                        }
+                    }
+                    AppEvent::DisconnectRequested => {
+                        if let Some(device) = ui_state.selected_device() {
+                            self.mode = AppMode::TryDisconnect(device);
+                        }
+                    }
+                    AppEvent::RemoveRequested => {
+                        if let Some(device) = ui_state.selected_device() {
+                            self.mode = AppMode::TryRemove(device);
+                        }
+                    }
+                    AppEvent::ShowHideUnnamed => {
+                        ui_state.show_unnamed = !ui_state.show_unnamed;
+                    }
+                    AppEvent::ShowBeacons => {
+                        ui_state.beacon_scroll = 0;
+                        self.mode = AppMode::Beacons;
+                    }
+                    AppEvent::ShowLog => {
+                        ui_state.log_view = LogView::default();
+                        self.mode = AppMode::Log;
+                    }
+                    AppEvent::FindCurrent => {
+                        if let Some(device) = ui_state.selected_device() {
+                            ui_state.finder = Some(Finder::new(&device));
+                            self.mode = AppMode::Find;
                         }
-                        AppEvent::InspectCurrent => {
-                            if let Some(idx) = ui_state.table_state.selected() {
-                                let device = self.devices.as_ref().borrow()[idx - 1].clone();
-                                self.mode = AppMode::Inspect(device);
-                                self.event_send_chan.send(AppEvent::Pass).await?;
+                    }
+                    AppEvent::InspectCurrent => {
+                        if let Some(device) = ui_state.selected_device() {
+                            self.mode = AppMode::Inspect(device);
+                            self.event_send_chan.send(AppEvent::Pass).await?;
+                        }
+                    }
+                    AppEvent::CycleDetails => ui_state.details = ui_state.details.next(),
+                    _ => {}
+                },
+                AppMode::Beacons => match e {
+                    AppEvent::ScrollDown => ui_state.beacon_scroll += 1,
+                    AppEvent::ScrollUp => {
+                        ui_state.beacon_scroll = ui_state.beacon_scroll.saturating_sub(1)
+                    }
+                    _ => {}
+                },
+                AppMode::Log => match e {
+                    AppEvent::ScrollUp => ui_state.log_view.scroll += 1,
+                    AppEvent::ScrollDown => {
+                        ui_state.log_view.scroll = ui_state.log_view.scroll.saturating_sub(1)
+                    }
+                    AppEvent::CycleLogSeverity => ui_state.log_view.cycle_severity(),
+                    AppEvent::CycleLogDevice => ui_state
+                        .log_view
+                        .cycle_device(&self.event_log.as_ref().borrow()),
+                    AppEvent::SaveLog => {
+                        let view = &ui_state.log_view;
+                        let saved = self
+                            .event_log
+                            .as_ref()
+                            .borrow()
+                            .save(view.min_severity, view.device.as_deref());
+
+                        let b = match saved {
+                            Ok(path) => Banner(
+                                format!("Saved log to {}", path.display()),
+                                BannerType::Success,
+                            ),
+                            Err(e) => {
+                                Banner(format!("Failed to save log: {}", e), BannerType::Failure)
                             }
+                        };
+                        self.set_new_banner(b, None).await;
+                    }
+                    _ => {}
+                },
+                AppMode::Find => {
+                    if let Some(finder) = &mut ui_state.finder {
+                        match e {
+                            AppEvent::ToggleBell => finder.bell = !finder.bell,
+                            AppEvent::Tick if finder.tick() => ring_bell(),
+                            _ => {}
                         }
-                        _ => {}
-                    },
-                    AppMode::Inspect(device) => {
-                        ui_state.inspect_text = Some(format_inspect_text(device.clone()).await);
                     }
-                    AppMode::TryConnect(device) => {
-                        if device.connected {
-                            let b = Banner(
-                                format!("{} already connected", device.name),
-                                BannerType::Status,
-                            );
-                            self.set_new_banner(b).await;
-                            self.mode = AppMode::Browse;
-                            continue;
-                        } else {
-                            let res = device.connect().await;
-
-                            match res {
-                                Ok(_) => {
-                                    let b = Banner(
-                                        format!("Successfully connected to {}", device.name),
-                                        BannerType::Success,
-                                    );
-                                    self.set_new_banner(b).await;
-
-                                    self.mode = AppMode::Browse;
-                                }
+                }
+                AppMode::Inspect(device) => match (&mut ui_state.inspect, &e) {
+                    (None, _) => {
+                        ui_state.inspect =
+                            Some(InspectView::new(format_inspect_fields(device).await))
+                    }
+                    // Only this device's updates change anything, skip the round trips to BlueZ
+                    // for the rest
+                    (
+                        Some(view),
+                        AppEvent::DeviceAdded(updated) | AppEvent::DeviceModified(updated),
+                    ) if updated.address == device.address => {
+                        view.fields = format_inspect_fields(updated.clone()).await;
+                        self.mode = AppMode::Inspect(updated.clone());
+                    }
+                    (Some(view), AppEvent::ScrollDown) => view.state.select_next(),
+                    (Some(view), AppEvent::ScrollUp) => view.state.select_previous(),
+                    (Some(view), AppEvent::PageDown) => view.state.scroll_down_by(view.page),
+                    (Some(view), AppEvent::PageUp) => view.state.scroll_up_by(view.page),
+                    (Some(view), AppEvent::CopyField) => {
+                        let b = match view.selected().and_then(|f| f.value.clone()) {
+                            Some(value) => match copy_to_clipboard(&value) {
+                                Ok(()) => Banner(format!("Copied {}", value), BannerType::Success),
                                 Err(e) => {
-                                    let b = Banner(
-                                        format!(
-                                            "Failed to connect to {}: {}",
-                                            device.name,
-                                            e.to_string()
-                                        ),
-                                        BannerType::Failure,
-                                    );
-                                    self.set_new_banner(b).await;
-
-                                    self.mode = AppMode::Browse;
+                                    Banner(format!("Failed to copy: {}", e), BannerType::Failure)
                                 }
+                            },
+                            None => Banner("Nothing to copy here".to_string(), BannerType::Status),
+                        };
+                        self.set_new_banner(b, None).await;
+                    }
+                    _ => {}
+                },
+                AppMode::TryConnect(device) => {
+                    if device.connected {
+                        let b = Banner(
+                            format!("{} already connected", device.name),
+                            BannerType::Status,
+                        );
+                        self.set_new_banner(b, Some(&device)).await;
+                        self.mode = AppMode::Browse;
+                        continue;
+                    } else {
+                        let res = device.connect().await;
+
+                        match res {
+                            Ok(_) => {
+                                let b = Banner(
+                                    format!("Successfully connected to {}", device.name),
+                                    BannerType::Success,
+                                );
+                                self.set_new_banner(b, Some(&device)).await;
+
+                                self.mode = AppMode::Browse;
+                            }
+                            Err(e) => {
+                                let b = Banner(
+                                    format!("Failed to connect to {}: {}", device.name, e),
+                                    BannerType::Failure,
+                                );
+                                self.notify(NotifyEvent::Failed, b.0.clone(), Urgency::Normal);
+                                self.set_new_banner(b, Some(&device)).await;
+
+                                self.mode = AppMode::Browse;
                             }
                         }
                     }
+                }
 
-                    AppMode::TryPair(device) => {
-                        if device.paired {
-                            let b = Banner(
-                                format!("{} already paired", device.name),
-                                BannerType::Status,
-                            );
-                            self.set_new_banner(b).await;
-                            self.mode = AppMode::Browse;
-                            continue;
-                        } else {
-                            let res = device.pair().await;
-
-                            match res {
-                                Ok(_) => {
-                                    let b = Banner(
-                                        format!("Successfully paired with {}", device.name),
-                                        BannerType::Success,
-                                    );
-                                    self.set_new_banner(b).await;
-
-                                    self.mode = AppMode::Browse;
-                                }
-                                Err(e) => {
-                                    let b = Banner(
-                                        format!(
-                                            "Failed to pair with {}: {}",
-                                            device.name,
-                                            e.to_string()
-                                        ),
-                                        BannerType::Failure,
-                                    );
-                                    self.set_new_banner(b).await;
-
-                                    self.mode = AppMode::Browse;
-                                }
+                AppMode::TryPair(device) => {
+                    if device.paired {
+                        let b = Banner(
+                            format!("{} already paired", device.name),
+                            BannerType::Status,
+                        );
+                        self.set_new_banner(b, Some(&device)).await;
+                        self.mode = AppMode::Browse;
+                        continue;
+                    } else {
+                        let res = device.pair().await;
+
+                        match res {
+                            Ok(_) => {
+                                let b = Banner(
+                                    format!("Successfully paired with {}", device.name),
+                                    BannerType::Success,
+                                );
+                                self.set_new_banner(b, Some(&device)).await;
+
+                                self.mode = AppMode::Browse;
+                            }
+                            Err(e) => {
+                                let b = Banner(
+                                    format!("Failed to pair with {}: {}", device.name, e),
+                                    BannerType::Failure,
+                                );
+                                self.notify(NotifyEvent::Failed, b.0.clone(), Urgency::Normal);
+                                self.set_new_banner(b, Some(&device)).await;
+
+                                self.mode = AppMode::Browse;
                             }
                         }
                     }
+                }
 
-                    AppMode::TryDisconnect(device) => {
-                        if !device.connected {
-                            let b = Banner(
-                                format!("{} is not connected", device.name),
-                                BannerType::Status,
-                            );
-                            self.set_new_banner(b).await;
-                            self.mode = AppMode::Browse;
-                            continue;
-                        } else {
-                            let res = device.disconnect().await;
-
-                            match res {
-                                Ok(_) => {
-                                    let b = Banner(
-                                        format!("Successfully disconnected from {}", device.name),
-                                        BannerType::Success,
-                                    );
-                                    self.set_new_banner(b).await;
-
-                                    self.mode = AppMode::Browse;
-                                }
-                                Err(e) => {
-                                    let b = Banner(
-                                        format!(
-                                            "Failed to disconnect from {}: {}",
-                                            device.name,
-                                            e.to_string()
-                                        ),
-                                        BannerType::Failure,
-                                    );
-                                    self.set_new_banner(b).await;
-
-                                    self.mode = AppMode::Browse;
-                                }
+                AppMode::TryDisconnect(device) => {
+                    if !device.connected {
+                        let b = Banner(
+                            format!("{} is not connected", device.name),
+                            BannerType::Status,
+                        );
+                        self.set_new_banner(b, Some(&device)).await;
+                        self.mode = AppMode::Browse;
+                        continue;
+                    } else {
+                        let res = device.disconnect().await;
+
+                        match res {
+                            Ok(_) => {
+                                let b = Banner(
+                                    format!("Successfully disconnected from {}", device.name),
+                                    BannerType::Success,
+                                );
+                                self.set_new_banner(b, Some(&device)).await;
+
+                                self.mode = AppMode::Browse;
+                            }
+                            Err(e) => {
+                                let b = Banner(
+                                    format!("Failed to disconnect from {}: {}", device.name, e),
+                                    BannerType::Failure,
+                                );
+                                self.notify(NotifyEvent::Failed, b.0.clone(), Urgency::Normal);
+                                self.set_new_banner(b, Some(&device)).await;
+
+                                self.mode = AppMode::Browse;
                             }
                         }
                     }
+                }
 
-                    AppMode::TryRemove(device) => {
-                        if !device.paired {
-                            let b = Banner(
-                                format!("{} is not paired", device.name),
-                                BannerType::Status,
-                            );
-                            self.set_new_banner(b).await;
-                            self.mode = AppMode::Browse;
-                            continue;
-                        } else {
-                            let res = device.remove().await;
-
-                            match res {
-                                Ok(_) => {
-                                    let b = Banner(
-                                        format!("Successfully removed device {}", device.name),
-                                        BannerType::Success,
-                                    );
-                                    self.set_new_banner(b).await;
-
-                                    self.mode = AppMode::Browse;
-                                }
-                                Err(e) => {
-                                    let b = Banner(
-                                        format!(
-                                            "Failed to remove device {}: {}",
-                                            device.name,
-                                            e.to_string()
-                                        ),
-                                        BannerType::Failure,
-                                    );
-                                    self.set_new_banner(b).await;
-
-                                    self.mode = AppMode::Browse;
-                                }
+                AppMode::TryRemove(device) => {
+                    if !device.paired {
+                        let b =
+                            Banner(format!("{} is not paired", device.name), BannerType::Status);
+                        self.set_new_banner(b, Some(&device)).await;
+                        self.mode = AppMode::Browse;
+                        continue;
+                    } else {
+                        let res = device.remove().await;
+
+                        match res {
+                            Ok(_) => {
+                                let b = Banner(
+                                    format!("Successfully removed device {}", device.name),
+                                    BannerType::Success,
+                                );
+                                self.set_new_banner(b, Some(&device)).await;
+
+                                self.mode = AppMode::Browse;
+                            }
+                            Err(e) => {
+                                let b = Banner(
+                                    format!("Failed to remove device {}: {}", device.name, e),
+                                    BannerType::Failure,
+                                );
+                                self.notify(NotifyEvent::Failed, b.0.clone(), Urgency::Normal);
+                                self.set_new_banner(b, Some(&device)).await;
+
+                                self.mode = AppMode::Browse;
                             }
                         }
                     }
                 }
+            }
 
-                ui_state.banner = self.banner.clone();
-                terminal.draw(|f| draw_ui(f, &mut ui_state, self.mode.clone()))?;
-            } else {
-                break;
+            if matches!(self.mode, AppMode::Browse) && ui_state.details != DetailsPane::Hidden {
+                refresh_details(&mut ui_state, &e).await;
             }
+
+            ui_state.banner = self.banner.clone();
+            terminal.draw(|f| draw_ui(f, &mut ui_state, self.mode.clone()))?;
         }
 
+        info!("Exiting");
+
         key_listener.abort();
-        bluetooth_listener.abort();
+        if let Some(listener) = &self.bluetooth_listener {
+            listener.abort();
+        }
+        ticker.abort();
+
+        Ok(())
+    }
+
+    /// Edit the command line, running the command on enter.
+    async fn command_key(&mut self, ui_state: &mut UIState, key: KeyEvent) -> Result<()> {
+        let Some(mut line) = ui_state.command.take() else {
+            return Ok(());
+        };
+
+        let devices = ui_state.listed_devices();
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        let open = match key.code {
+            KeyCode::Esc | KeyCode::Enter => false,
+            KeyCode::Char('c') if ctrl => false,
+            KeyCode::Backspace => line.input.pop().is_some(),
+            KeyCode::Tab => {
+                line.complete(&devices);
+                true
+            }
+            KeyCode::Down => {
+                let count = line.suggestions(&devices).len();
+                line.selected = (line.selected + 1).min(count.saturating_sub(1));
+                true
+            }
+            KeyCode::Up => {
+                line.selected = line.selected.saturating_sub(1);
+                true
+            }
+            KeyCode::Char(c) if !ctrl => {
+                line.input.push(c);
+                line.selected = 0;
+                true
+            }
+            _ => true,
+        };
+
+        if open {
+            ui_state.command = Some(line);
+            return Ok(());
+        }
+
+        self.typing.store(false, Ordering::Relaxed);
+
+        if key.code == KeyCode::Enter {
+            self.run_command(ui_state, &line.input).await?;
+        }
 
         Ok(())
     }
 
-    async fn set_new_banner(&mut self, b: Banner) {
+    /// Run the control that was clicked, or select the device, acting on it when it's double
+    /// clicked.
+    async fn click(&mut self, ui_state: &mut UIState, x: u16, y: u16) -> Result<()> {
+        let position = Position::new(x, y);
+
+        if let Some((_, action)) = ui_state
+            .hit_areas
+            .controls
+            .iter()
+            .find(|(rect, _)| rect.contains(position))
+        {
+            if action.available(&self.mode) {
+                self.event_send_chan.send(action.event()).await?;
+            }
+            return Ok(());
+        }
+
+        let table = ui_state.hit_areas.table;
+
+        if !matches!(self.mode, AppMode::Browse) || !table.contains(position) {
+            return Ok(());
+        }
+
+        // The header is the first row
+        let row = ui_state.table_state.offset() + (y - table.y) as usize;
+        let Some(device) = row
+            .checked_sub(1)
+            .and_then(|idx| ui_state.listed_devices().into_iter().nth(idx))
+        else {
+            return Ok(());
+        };
+
+        ui_state.select_device(&device);
+
+        let double_click = ui_state
+            .last_click
+            .as_ref()
+            .is_some_and(|(t, address)| t.elapsed() < DOUBLE_CLICK && *address == device.address);
+
+        if double_click {
+            ui_state.last_click = None;
+
+            let event = if device.connected {
+                AppEvent::DisconnectRequested
+            } else {
+                AppEvent::ConnectRequested
+            };
+            self.event_send_chan.send(event).await?;
+        } else {
+            ui_state.last_click = Some((Instant::now(), device.address));
+        }
+
+        Ok(())
+    }
+
+    /// Run a command from the command line, going through the same events as the keys where
+    /// there's one for it.
+    async fn run_command(&mut self, ui_state: &mut UIState, input: &str) -> Result<()> {
+        let command = match input.parse::<Command>() {
+            Ok(command) => command,
+            Err(e) => {
+                self.set_new_banner(Banner(e, BannerType::Failure), None)
+                    .await;
+                return Ok(());
+            }
+        };
+
+        match command {
+            Command::Action(action, Some(query)) => {
+                let devices = ui_state.listed_devices();
+
+                let device = match find_device(&query, &devices, action) {
+                    Ok(device) => device,
+                    Err(e) => {
+                        self.set_new_banner(Banner(e, BannerType::Failure), None)
+                            .await;
+                        return Ok(());
+                    }
+                };
+
+                // Select the device and act on it as if from the device list
+                ui_state.select_device(device);
+                self.event_send_chan.send(AppEvent::Esc).await?;
+                self.event_send_chan.send(action.event()).await?;
+            }
+            Command::Action(action, None) => self.event_send_chan.send(action.event()).await?,
+            Command::Filter(filters) => {
+                ui_state.filters = filters;
+                *ui_state.table_state.selected_mut() = None;
+            }
+            Command::Sort(order) => {
+                self.config.sort = order;
+                self.devices
+                    .as_ref()
+                    .borrow_mut()
+                    .sort_by(|a, b| order.compare(a, b));
+            }
+            Command::Adapter(name) => self.switch_adapter(ui_state, name).await?,
+        }
+
+        Ok(())
+    }
+
+    /// Restart discovery on another adapter, forgetting everything seen on the current one.
+    async fn switch_adapter(&mut self, ui_state: &mut UIState, name: String) -> Result<()> {
+        let adapters = match bluer::Session::new().await {
+            Ok(session) => session.adapter_names().await.unwrap_or_default(),
+            Err(_) => vec![],
+        };
+
+        if !adapters.contains(&name) {
+            let b = Banner(
+                format!("No adapter {} (found {})", name, adapters.join(", ")),
+                BannerType::Failure,
+            );
+            self.set_new_banner(b, None).await;
+            return Ok(());
+        }
+
+        if let Some(listener) = self.bluetooth_listener.take() {
+            listener.abort();
+        }
+
+        // Nothing seen through the old adapter says anything about the new one
+        self.mode = AppMode::Browse;
+        self.devices.as_ref().borrow_mut().clear();
+        self.histories.as_ref().borrow_mut().clear();
+        *self.beacons.as_ref().borrow_mut() = BeaconTracker::default();
+        self.battery_alerts = BatteryAlerts::new(&self.config.battery);
+        *ui_state.table_state.selected_mut() = None;
+        ui_state.inspect = None;
+        ui_state.finder = None;
+        ui_state.details_fields = None;
+        ui_state.beacon_scroll = 0;
+
+        self.config.adapter = Some(name.clone());
+        self.bluetooth_listener =
+            Some(launch_bluetooth_listener(self.get_event_chan_handle(), &self.config).await);
+
+        let b = Banner(format!("Using adapter {}", name), BannerType::Status);
+        self.set_new_banner(b, None).await;
+
+        Ok(())
+    }
+
+    /// Warn when a device's battery drops below one of its alert thresholds.
+    async fn check_battery(&mut self, device: &BTDevice) {
+        let Some(alert) = self.battery_alerts.check(device) else {
+            return;
+        };
+
+        let msg = format!(
+            "{} battery low: {}% (alert at {}%)",
+            device.name, alert.level, alert.threshold
+        );
+
+        write_event(&alert.to_json(&device.address, &device.name));
+
+        let urgency = if alert.critical {
+            Urgency::Critical
+        } else {
+            Urgency::Normal
+        };
+
+        self.notify(NotifyEvent::LowBattery, msg.clone(), urgency);
+        self.set_new_banner(Banner(msg, BannerType::Failure), Some(device))
+            .await;
+    }
+
+    /// Notify about connection and pairing changes between `device` and what we last saw.
+    fn notify_transitions(&self, device: &BTDevice) {
+        let devices = self.devices.as_ref().borrow();
+        let Some(previous) = devices.iter().find(|d| d.address == device.address) else {
+            return;
+        };
+
+        if device.connected && !previous.connected {
+            self.notify(
+                NotifyEvent::Connected,
+                format!("Connected to {}", device.name),
+                Urgency::Normal,
+            );
+        } else if !device.connected && previous.connected {
+            self.notify(
+                NotifyEvent::Disconnected,
+                format!("{} disconnected", device.name),
+                Urgency::Normal,
+            );
+        }
+
+        if device.paired && !previous.paired {
+            self.notify(
+                NotifyEvent::Paired,
+                format!("Paired with {}", device.name),
+                Urgency::Normal,
+            );
+        }
+    }
+
+    fn notify(&self, event: NotifyEvent, body: String, urgency: Urgency) {
+        if let Some(notifier) = &self.notifier {
+            notifier.notify(event, body, urgency);
+        }
+    }
+
+    fn log(&self, severity: Severity, device: Option<&BTDevice>, message: String) {
+        self.event_log
+            .as_ref()
+            .borrow_mut()
+            .push(severity, device, message);
+    }
+
+    /// Show a banner, and record it in the event log against `device`.
+    async fn set_new_banner(&mut self, b: Banner, device: Option<&BTDevice>) {
         let chan = self.get_event_chan_handle();
 
-        let duration = match b.1 {
-            BannerType::Failure => Duration::from_secs(4),
-            _ => Duration::from_secs(3),
+        let severity = match b.1 {
+            BannerType::Success => {
+                info!("{}", b.0);
+                Severity::Success
+            }
+            BannerType::Failure => {
+                warn!("{}", b.0);
+                Severity::Error
+            }
+            BannerType::Status => Severity::Info,
         };
+        self.log(severity, device, b.0.clone());
+
+        let duration = Duration::from_secs_f64(match b.1 {
+            BannerType::Success => self.config.banner.success,
+            BannerType::Failure => self.config.banner.failure,
+            BannerType::Status => self.config.banner.status,
+        });
 
         self.banner = Some(b.clone());
 
@@ -393,3 +934,31 @@ impl BluetoolApp {
         });
     }
 }
+
+/// Keep the details pane on the highlighted device, regenerating them when it changes or
+/// that device sends an update.
+async fn refresh_details(ui_state: &mut UIState, e: &AppEvent) {
+    let Some(device) = ui_state.selected_device() else {
+        ui_state.details_fields = None;
+        return;
+    };
+
+    let stale = match (&ui_state.details_fields, e) {
+        (None, _) => true,
+        (Some((address, _)), _) if *address != device.address => true,
+        (_, AppEvent::DeviceAdded(d) | AppEvent::DeviceModified(d)) => d.address == device.address,
+        _ => false,
+    };
+
+    if stale {
+        let fields = format_inspect_fields(device.clone()).await;
+        ui_state.details_fields = Some((device.address, fields));
+    }
+}
+
+/// Ring the terminal bell.
+fn ring_bell() {
+    let mut out = stdout();
+    let _ = out.write_all(b"\x07");
+    let _ = out.flush();
+}
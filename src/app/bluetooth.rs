@@ -5,31 +5,55 @@ use bluer::{
     AdapterEvent, Address, Device, DeviceEvent, DiscoveryFilter, DiscoveryTransport, Uuid,
 };
 use futures::{pin_mut, stream::SelectAll, StreamExt};
+use log::{debug, error, info, trace};
 use std::{
     cmp::Ordering,
-    collections::HashSet,
-    env,
+    collections::{HashMap, HashSet},
     hash::{Hash, Hasher},
     sync::Arc,
 };
 use tokio::{sync::mpsc::Sender, task::JoinHandle};
 
+use crate::{
+    config::{Config, Transport},
+    decode::{
+        company_name, sig_uuid, AddressKind, Appearance, DeviceClass, ProximityPairing,
+        APPLE_COMPANY_ID, BATTERY_LEVEL, BATTERY_SERVICE,
+    },
+};
+
 use super::bluetool::AppEvent;
 
 #[derive(Debug, Clone)]
 pub struct BTDevice {
     pub inner: Device,
     pub name: String,
+    /// The name set locally with `bluetoothctl set-alias`, or the device's own name.
+    pub alias: String,
     pub icon_name: String,
+    pub class: Option<DeviceClass>,
+    pub appearance: Option<Appearance>,
     pub paired: bool,
     pub connected: bool,
     pub address: String,
+    pub address_kind: AddressKind,
     pub battery: Option<u8>,
+    /// Battery and in-ear state advertised by AirPods and Beats headphones.
+    pub apple_accessory: Option<ProximityPairing>,
     pub rssi: Option<i16>,
+    pub tx_power: Option<i16>,
+    pub manufacturer_data: HashMap<u16, Vec<u8>>,
+    pub service_data: HashMap<Uuid, Vec<u8>>,
 }
 
 impl BTDevice {
     pub async fn new(device: &Device) -> Self {
+        let manufacturer_data = device
+            .manufacturer_data()
+            .await
+            .unwrap_or(None)
+            .unwrap_or_default();
+
         BTDevice {
             inner: device.clone(),
             name: device
@@ -37,11 +61,14 @@ impl BTDevice {
                 .await
                 .unwrap_or(None)
                 .unwrap_or("???".to_string()),
+            alias: device.alias().await.unwrap_or_default(),
             icon_name: device
                 .icon()
                 .await
                 .unwrap_or(None)
                 .unwrap_or("".to_string()),
+            class: device.class().await.unwrap_or(None).map(DeviceClass),
+            appearance: device.appearance().await.unwrap_or(None).map(Appearance),
             address: device
                 .address()
                 .0
@@ -50,32 +77,88 @@ impl BTDevice {
                 .map(|u| format!("{:02x}", u))
                 .collect::<Vec<String>>()
                 .join(":"),
+            address_kind: AddressKind::classify(device.address(), device.address_type().await.ok()),
 
             paired: device.is_paired().await.unwrap_or(false),
             connected: device.is_connected().await.unwrap_or(false),
             battery: check_battery_service(device).await,
+            apple_accessory: manufacturer_data
+                .get(&APPLE_COMPANY_ID)
+                .and_then(|data| ProximityPairing::from_manufacturer_data(data)),
             rssi: device.rssi().await.unwrap_or(None),
+            tx_power: device.tx_power().await.unwrap_or(None),
+            manufacturer_data,
+            service_data: device
+                .service_data()
+                .await
+                .unwrap_or(None)
+                .unwrap_or_default(),
         }
     }
 
     pub async fn connect(&self) -> Result<()> {
+        info!("Connecting to {} ({})", self.name, self.address);
         Ok(self.inner.connect().await?)
     }
 
     pub async fn pair(&self) -> Result<()> {
+        info!("Pairing with {} ({})", self.name, self.address);
         Ok(self.inner.pair().await?)
     }
 
     pub async fn disconnect(&self) -> Result<()> {
+        info!("Disconnecting from {} ({})", self.name, self.address);
         Ok(self.inner.disconnect().await?)
     }
 
     pub async fn remove(&self) -> Result<()> {
+        info!("Removing {} ({})", self.name, self.address);
         let session = bluer::Session::new().await?;
-        let adapter = session.default_adapter().await?;
+        let adapter = session.adapter(self.inner.adapter_name())?;
         Ok(adapter.remove_device(self.inner.address()).await?)
     }
 
+    /// The device's battery level, or the lowest earbud for AirPods and Beats which don't
+    /// report a single one.
+    pub fn battery_level(&self) -> Option<u8> {
+        self.battery.or_else(|| {
+            self.apple_accessory.and_then(|a| {
+                a.levels()
+                    .into_iter()
+                    .filter(|(label, _)| *label != "C")
+                    .map(|(_, level)| level)
+                    .min()
+            })
+        })
+    }
+
+    /// The OUI vendor of a public address, or else the company in the manufacturer data.
+    pub fn vendor(&self) -> Option<&'static str> {
+        match self.address_kind {
+            AddressKind::Public(Some(vendor)) => Some(vendor),
+            _ => self
+                .manufacturer_data
+                .keys()
+                .min()
+                .and_then(|&id| company_name(id)),
+        }
+    }
+
+    /// Whether the device was seen over BR/EDR, LE or both, going by what it advertised.
+    pub fn transport(&self) -> Option<&'static str> {
+        let bredr = self.class.is_some();
+        let le = self.appearance.is_some()
+            || !self.service_data.is_empty()
+            || !matches!(self.address_kind, AddressKind::Public(_));
+
+        match (bredr, le) {
+            (true, true) => Some("Dual"),
+            (true, false) => Some("BR/EDR"),
+            (false, true) => Some("LE"),
+            (false, false) => None,
+        }
+    }
+
     fn sort_value(&self) -> (i32, &str) {
         (
             self.connected as i32 * 2000 + self.paired as i32 * 1000,
@@ -88,10 +171,10 @@ async fn check_battery_service(device: &Device) -> Option<u8> {
     // Fuck this is nasty though
     if let Ok(services) = device.services().await {
         for service in services {
-            if service.uuid().await.unwrap() == Uuid::from_u128(0x180F) {
+            if service.uuid().await.unwrap() == sig_uuid(BATTERY_SERVICE) {
                 if let Ok(characteristics) = service.characteristics().await {
                     for characteristic in characteristics {
-                        if characteristic.uuid().await.unwrap() == Uuid::from_u128(0x2A19) {
+                        if characteristic.uuid().await.unwrap() == sig_uuid(BATTERY_LEVEL) {
                             if let Ok(value) = characteristic.read().await {
                                 return Some(value[0]);
                             }
@@ -107,73 +190,95 @@ async fn check_battery_service(device: &Device) -> Option<u8> {
 
 pub async fn launch_bluetooth_listener(
     event_send_chan: Arc<Sender<AppEvent>>,
+    config: &Config,
 ) -> JoinHandle<Result<()>> {
+    let adapter_name = config.adapter.clone();
+    let transport = config.transport;
+    let filter_addr: HashSet<Address> = config.only.iter().map(|a| a.0).collect();
+
     tokio::spawn(async move {
-        // let with_changes = env::args().any(|arg| arg == "--changes");
-        // let all_properties = env::args().any(|arg| arg == "--all-properties");
-        let le_only = env::args().any(|arg| arg == "--le");
-        let br_edr_only = env::args().any(|arg| arg == "--bredr");
-        let filter_addr: HashSet<_> = env::args()
-            .filter_map(|arg| arg.parse::<Address>().ok())
-            .collect();
-        let session = bluer::Session::new().await?;
-        let adapter = session.default_adapter().await?;
+        let result = listen(event_send_chan, adapter_name, transport, filter_addr).await;
+
+        if let Err(e) = &result {
+            error!("Bluetooth listener stopped: {}", e);
+        }
 
-        adapter.set_powered(true).await?;
+        result
+    })
+}
 
-        let filter = DiscoveryFilter {
-            transport: if le_only {
-                DiscoveryTransport::Le
-            } else if br_edr_only {
-                DiscoveryTransport::BrEdr
-            } else {
-                DiscoveryTransport::Auto
-            },
-            ..Default::default()
-        };
+async fn listen(
+    event_send_chan: Arc<Sender<AppEvent>>,
+    adapter_name: Option<String>,
+    transport: Transport,
+    filter_addr: HashSet<Address>,
+) -> Result<()> {
+    let session = bluer::Session::new().await?;
+    let adapter = match adapter_name {
+        Some(name) => session.adapter(&name)?,
+        None => session.default_adapter().await?,
+    };
 
-        adapter.set_discovery_filter(filter).await?;
+    info!("Using adapter {}", adapter.name());
 
-        let device_events = adapter.discover_devices().await?;
+    adapter.set_powered(true).await?;
 
-        pin_mut!(device_events);
+    let filter = DiscoveryFilter {
+        transport: match transport {
+            Transport::Auto => DiscoveryTransport::Auto,
+            Transport::Le => DiscoveryTransport::Le,
+            Transport::Bredr => DiscoveryTransport::BrEdr,
+        },
+        ..Default::default()
+    };
 
-        let mut all_change_events = SelectAll::new();
+    debug!("Discovery filter: {:?}", filter);
+    adapter.set_discovery_filter(filter).await?;
 
-        loop {
-            tokio::select! {
-                Some(device_event) = device_events.next() => {
-                    match device_event {
-                        AdapterEvent::DeviceAdded(addr) => {
-                            if !filter_addr.is_empty() && !filter_addr.contains(&addr) {
-                                continue;
-                            }
+    let device_events = adapter.discover_devices().await?;
 
-                            let device = adapter.device(addr).unwrap();
+    pin_mut!(device_events);
 
-                            event_send_chan.send(AppEvent::DeviceAdded(BTDevice::new(&device).await)).await.unwrap();
+    let mut all_change_events = SelectAll::new();
 
-                            let change_events = device.events().await?.map(move |evt| (addr, evt));
-                            all_change_events.push(change_events);
-                        },
-                        AdapterEvent::DeviceRemoved(addr) => {
-                            let device = adapter.device(addr).unwrap();
-                            event_send_chan.send(AppEvent::DeviceRemoved(BTDevice::new(&device).await)).await.unwrap();
+    loop {
+        tokio::select! {
+            Some(device_event) = device_events.next() => {
+                match device_event {
+                    AdapterEvent::DeviceAdded(addr) => {
+                        if !filter_addr.is_empty() && !filter_addr.contains(&addr) {
+                            continue;
                         }
-                        _ => {},
+
+                        debug!("Device added: {}", addr);
+                        let device = adapter.device(addr).unwrap();
+
+                        event_send_chan.send(AppEvent::DeviceAdded(BTDevice::new(&device).await)).await.unwrap();
+
+                        let change_events = device.events().await?.map(move |evt| (addr, evt));
+                        all_change_events.push(change_events);
+                    },
+                    AdapterEvent::DeviceRemoved(addr) => {
+                        debug!("Device removed: {}", addr);
+                        let device = adapter.device(addr).unwrap();
+                        event_send_chan.send(AppEvent::DeviceRemoved(BTDevice::new(&device).await)).await.unwrap();
                     }
+                    _ => {},
                 }
-                Some((addr, DeviceEvent::PropertyChanged(_))) = all_change_events.next() => {
-                    let device = adapter.device(addr).unwrap();
-                    event_send_chan.send(AppEvent::DeviceModified(BTDevice::new(&device).await)).await.unwrap();
+            }
+            Some((addr, DeviceEvent::PropertyChanged(property))) = all_change_events.next() => {
+                trace!("{} changed: {:?}", addr, property);
+                let device = adapter.device(addr).unwrap();
+                event_send_chan.send(AppEvent::DeviceModified(BTDevice::new(&device).await)).await.unwrap();
 
-                }
-                else => break
             }
+            else => break
         }
+    }
 
-        Ok(())
-    })
+    info!("Discovery stream ended");
+
+    Ok(())
 }
 
 impl Eq for BTDevice {}
@@ -0,0 +1,639 @@
+//! The `:` command line, for acting on devices by name instead of scrolling to them.
+
+use std::{fmt, str::FromStr};
+
+use crate::config::SortOrder;
+
+use super::{bluetooth::BTDevice, keymap::Action};
+
+/// Actions which act on a device, and can be given one to find by name or address.
+const DEVICE_ACTIONS: [Action; 6] = [
+    Action::Connect,
+    Action::Pair,
+    Action::Disconnect,
+    Action::Remove,
+    Action::Inspect,
+    Action::Find,
+];
+
+/// Completions for `:filter`.
+const FILTER_HINTS: [&str; 7] = [
+    "connected",
+    "paired",
+    "name=",
+    "rssi>",
+    "rssi<",
+    "battery<",
+    "battery>",
+];
+
+pub enum Command {
+    /// Run an action, on the device best matching the query if one's given.
+    Action(Action, Option<String>),
+    /// Only list devices matching every filter.
+    Filter(Vec<DeviceFilter>),
+    Sort(SortOrder),
+    Adapter(String),
+}
+
+/// Every command name, which are the actions that make sense to type plus a few of their own.
+fn command_names() -> impl Iterator<Item = &'static str> + Clone {
+    Action::ALL
+        .iter()
+        .filter(|a| {
+            !matches!(
+                a,
+                Action::Down
+                    | Action::Up
+                    | Action::PageDown
+                    | Action::PageUp
+                    | Action::Back
+                    | Action::Command
+            )
+        })
+        .map(Action::name)
+        .chain(["filter", "sort", "adapter"])
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim();
+        let (name, arg) = line
+            .split_once(' ')
+            .map_or((line, ""), |(name, arg)| (name, arg.trim()));
+
+        if name.is_empty() {
+            return Err("Type a command, e.g. connect <device>".to_string());
+        }
+
+        let name = resolve(name, command_names(), "command")?;
+
+        match name {
+            "filter" => {
+                let mut filters = vec![];
+
+                for word in arg.split_whitespace() {
+                    match (word.parse(), filters.last_mut()) {
+                        (Ok(filter), _) => filters.push(filter),
+                        // Names can have spaces in them
+                        (Err(_), Some(DeviceFilter::Name(name))) => {
+                            name.push(' ');
+                            name.push_str(&word.to_lowercase());
+                        }
+                        (Err(e), _) => return Err(format!("Invalid filter: {}", e)),
+                    }
+                }
+
+                Ok(Command::Filter(filters))
+            }
+            "sort" if arg.is_empty() => Err("sort needs an order, e.g. signal".to_string()),
+            "sort" => resolve(
+                arg,
+                SortOrder::ALL.iter().map(SortOrder::name),
+                "sort order",
+            )
+            .map(|name| {
+                Command::Sort(
+                    SortOrder::ALL
+                        .into_iter()
+                        .find(|o| o.name() == name)
+                        .unwrap(),
+                )
+            }),
+            "adapter" if arg.is_empty() => Err("adapter needs a name, e.g. hci1".to_string()),
+            "adapter" => Ok(Command::Adapter(arg.to_string())),
+            name => {
+                let action = Action::ALL.into_iter().find(|a| a.name() == name).unwrap();
+                let target = DEVICE_ACTIONS.contains(&action) && !arg.is_empty();
+
+                Ok(Command::Action(action, target.then(|| arg.to_string())))
+            }
+        }
+    }
+}
+
+/// A condition a device must meet to be listed, e.g. `rssi>-70`, `battery<=20`, `name=sony`
+/// or `paired`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeviceFilter {
+    Connected,
+    Paired,
+    /// Names containing the text, ignoring case.
+    Name(String),
+    Rssi(Comparison, i16),
+    Battery(Comparison, u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl Comparison {
+    fn test<T: Ord>(&self, a: T, b: T) -> bool {
+        match self {
+            Comparison::Less => a < b,
+            Comparison::LessOrEqual => a <= b,
+            Comparison::Equal => a == b,
+            Comparison::GreaterOrEqual => a >= b,
+            Comparison::Greater => a > b,
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match self {
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Equal => "=",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Greater => ">",
+        }
+    }
+}
+
+impl DeviceFilter {
+    pub fn matches(&self, device: &BTDevice) -> bool {
+        match self {
+            DeviceFilter::Connected => device.connected,
+            DeviceFilter::Paired => device.paired,
+            DeviceFilter::Name(text) => device.name.to_lowercase().contains(text),
+            DeviceFilter::Rssi(cmp, value) => device.rssi.is_some_and(|r| cmp.test(r, *value)),
+            DeviceFilter::Battery(cmp, value) => {
+                device.battery_level().is_some_and(|b| cmp.test(b, *value))
+            }
+        }
+    }
+}
+
+impl FromStr for DeviceFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "connected" => return Ok(DeviceFilter::Connected),
+            "paired" => return Ok(DeviceFilter::Paired),
+            _ => {}
+        }
+
+        let split = s
+            .find(['<', '>', '='])
+            .ok_or_else(|| format!("expected a comparison like rssi>-70, not `{}`", s))?;
+        let (field, rest) = s.split_at(split);
+
+        let (cmp, value) = [
+            Comparison::LessOrEqual,
+            Comparison::GreaterOrEqual,
+            Comparison::Less,
+            Comparison::Greater,
+            Comparison::Equal,
+        ]
+        .into_iter()
+        .find_map(|cmp| rest.strip_prefix(cmp.symbol()).map(|v| (cmp, v.trim())))
+        .ok_or_else(|| format!("invalid comparison in `{}`", s))?;
+
+        let invalid = |_| format!("invalid value `{}` in `{}`", value, s);
+
+        match field.trim().to_lowercase().as_str() {
+            "name" if cmp == Comparison::Equal => Ok(DeviceFilter::Name(value.to_lowercase())),
+            "rssi" => Ok(DeviceFilter::Rssi(cmp, value.parse().map_err(invalid)?)),
+            "battery" => Ok(DeviceFilter::Battery(
+                cmp,
+                value.trim_end_matches('%').parse().map_err(invalid)?,
+            )),
+            _ => Err(format!("can't filter on `{}`", s)),
+        }
+    }
+}
+
+impl fmt::Display for DeviceFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceFilter::Connected => write!(f, "connected"),
+            DeviceFilter::Paired => write!(f, "paired"),
+            DeviceFilter::Name(text) => write!(f, "name={}", text),
+            DeviceFilter::Rssi(cmp, v) => write!(f, "rssi{}{}", cmp.symbol(), v),
+            DeviceFilter::Battery(cmp, v) => write!(f, "battery{}{}", cmp.symbol(), v),
+        }
+    }
+}
+
+/// What's being typed on the command line.
+#[derive(Debug, Clone, Default)]
+pub struct CommandLine {
+    pub input: String,
+    /// Index of the highlighted suggestion.
+    pub selected: usize,
+}
+
+impl CommandLine {
+    /// Completions for the word being typed, best match first.
+    pub fn suggestions<'a>(&'a self, devices: &'a [BTDevice]) -> Vec<&'a str> {
+        self.completing(devices).1
+    }
+
+    /// Replace the word being typed with the highlighted suggestion.
+    pub fn complete(&mut self, devices: &[BTDevice]) {
+        let (start, suggestions) = self.completing(devices);
+
+        if let Some(choice) = suggestions.get(self.selected).map(|c| c.to_string()) {
+            self.input.truncate(start);
+            self.input.push_str(&choice);
+
+            // Move straight on to the command's argument
+            if start == 0 {
+                self.input.push(' ');
+            }
+
+            self.selected = 0;
+        }
+    }
+
+    /// Where the word being typed starts, and what it could be completed to.
+    fn completing<'a>(&'a self, devices: &'a [BTDevice]) -> (usize, Vec<&'a str>) {
+        let Some((name, arg)) = self.input.split_once(' ') else {
+            return (0, rank(&self.input, command_names()));
+        };
+
+        let arg_start = self.input.len() - arg.trim_start().len();
+        let arg = arg.trim_start();
+
+        match resolve(name, command_names(), "command").ok() {
+            Some("sort") => (
+                arg_start,
+                rank(arg, SortOrder::ALL.iter().map(SortOrder::name)),
+            ),
+            Some("filter") => {
+                let word = arg.rsplit(' ').next().unwrap_or("");
+                (self.input.len() - word.len(), rank(word, FILTER_HINTS))
+            }
+            Some(name) if DEVICE_ACTIONS.iter().any(|a| a.name() == name) => {
+                (arg_start, rank(arg, devices.iter().map(device_label)))
+            }
+            _ => (self.input.len(), vec![]),
+        }
+    }
+}
+
+/// Actions which can't be undone, and so aren't run on a guess between several devices.
+const DESTRUCTIVE_ACTIONS: [Action; 2] = [Action::Disconnect, Action::Remove];
+
+/// How close a runner-up's score has to be to the best for a name to count as ambiguous.
+const AMBIGUOUS_SCORE: i32 = 3;
+
+/// The device for `action` whose address is or starts with `query`, or whose name best matches
+/// it.
+pub fn find_device<'a>(
+    query: &str,
+    devices: &'a [BTDevice],
+    action: Action,
+) -> Result<&'a BTDevice, String> {
+    let targets = devices
+        .iter()
+        .map(|d| (d.address.as_str(), device_label(d)))
+        .collect::<Vec<(&str, &str)>>();
+
+    find_target(query, &targets, DESTRUCTIVE_ACTIONS.contains(&action)).map(|i| &devices[i])
+}
+
+/// Index of the `(address, label)` target `query` picks out. A name only has to match best,
+/// unless `careful`, when it has to match clearly better than any other.
+fn find_target(query: &str, targets: &[(&str, &str)], careful: bool) -> Result<usize, String> {
+    if let Some(i) = targets
+        .iter()
+        .position(|(address, _)| address.eq_ignore_ascii_case(query))
+    {
+        return Ok(i);
+    }
+
+    let by_address = targets
+        .iter()
+        .enumerate()
+        .filter(|(_, (address, _))| {
+            address
+                .get(..query.len())
+                .is_some_and(|a| a.eq_ignore_ascii_case(query))
+        })
+        .map(|(i, _)| i)
+        .collect::<Vec<usize>>();
+
+    match by_address[..] {
+        [i] => return Ok(i),
+        [] => {}
+        _ => {
+            return Err(format!(
+                "`{}` starts several addresses: {}",
+                query,
+                list_targets(targets, &by_address)
+            ))
+        }
+    }
+
+    let mut by_name = targets
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (_, label))| fuzzy_score(query, label).map(|score| (score, i)))
+        .collect::<Vec<(i32, usize)>>();
+    by_name.sort_by_key(|(score, _)| -score);
+
+    let Some(&(best, i)) = by_name.first() else {
+        return Err(format!("No device matches `{}`", query));
+    };
+
+    let close = by_name
+        .iter()
+        .take_while(|(score, _)| best - score < AMBIGUOUS_SCORE)
+        .map(|(_, i)| *i)
+        .collect::<Vec<usize>>();
+
+    if careful && close.len() > 1 {
+        return Err(format!(
+            "`{}` could be {}, give more of the name or the address",
+            query,
+            list_targets(targets, &close)
+        ));
+    }
+
+    Ok(i)
+}
+
+fn list_targets(targets: &[(&str, &str)], indices: &[usize]) -> String {
+    indices
+        .iter()
+        .map(|&i| targets[i].1)
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+/// Unnamed devices go by their address.
+fn device_label(device: &BTDevice) -> &str {
+    if device.name == "???" {
+        &device.address
+    } else {
+        &device.name
+    }
+}
+
+/// The candidate equal to `query`, or else the only one it's the start of, ignoring case.
+/// `what` names the kind of candidate for the error.
+fn resolve<'a>(
+    query: &str,
+    candidates: impl IntoIterator<Item = &'a str> + Clone,
+    what: &str,
+) -> Result<&'a str, String> {
+    if let Some(exact) = candidates
+        .clone()
+        .into_iter()
+        .find(|c| c.eq_ignore_ascii_case(query))
+    {
+        return Ok(exact);
+    }
+
+    let prefixed = candidates
+        .into_iter()
+        .filter(|c| {
+            c.get(..query.len())
+                .is_some_and(|p| p.eq_ignore_ascii_case(query))
+        })
+        .collect::<Vec<&str>>();
+
+    match prefixed[..] {
+        [only] => Ok(only),
+        [] => Err(format!("Unknown {} `{}`", what, query)),
+        _ => Err(format!(
+            "Ambiguous {} `{}`, could be {}",
+            what,
+            query,
+            prefixed.join(", ")
+        )),
+    }
+}
+
+/// Candidates matching `query`, best first and shortest first among equals.
+fn rank<'a>(query: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let mut matches = candidates
+        .into_iter()
+        .filter_map(|c| fuzzy_score(query, c).map(|score| (score, c)))
+        .collect::<Vec<(i32, &str)>>();
+
+    matches.sort_by_key(|(score, c)| (-score, c.len(), *c));
+    matches.dedup_by_key(|(_, c)| *c);
+
+    matches.into_iter().map(|(_, c)| c).collect()
+}
+
+/// Score how well `query` matches `candidate` as a subsequence ignoring case, or `None` if it
+/// doesn't. Matches at the start of words and runs of consecutive characters score higher.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let candidate = candidate.to_lowercase().chars().collect::<Vec<char>>();
+
+    let mut score = 0;
+    let mut next = 0;
+    let mut last: Option<usize> = None;
+
+    for q in query.to_lowercase().chars() {
+        let i = (next..candidate.len()).find(|&i| candidate[i] == q)?;
+
+        score += 1;
+
+        if i == 0 {
+            score += 8;
+        } else if matches!(candidate[i - 1], ' ' | '-' | '_' | ':') {
+            score += 4;
+        }
+
+        if last.is_some_and(|l| l + 1 == i) {
+            score += 5;
+        }
+
+        score -= (i - next) as i32;
+        last = Some(i);
+        next = i + 1;
+    }
+
+    Some(score)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Result<Command, String> {
+        line.parse()
+    }
+
+    #[test]
+    fn scores() {
+        assert!(fuzzy_score("cnt", "connect").is_some());
+        assert!(fuzzy_score("CON", "connect").is_some());
+        assert_eq!(fuzzy_score("tc", "connect"), None);
+        assert_eq!(fuzzy_score("connects", "connect"), None);
+
+        // Matching the start scores higher than the middle
+        assert!(fuzzy_score("con", "connect") > fuzzy_score("con", "disconnect"));
+        // Then the start of a word
+        assert!(fuzzy_score("wh", "Sony WH-1000XM4") > fuzzy_score("wh", "Bowhead"));
+        // And runs of characters over scattered ones
+        assert!(fuzzy_score("pad", "iPad") > fuzzy_score("pad", "Pixel Buds"));
+    }
+
+    #[test]
+    fn ranking() {
+        assert_eq!(rank("dis", command_names())[0], "disconnect");
+        assert_eq!(rank("log", command_names())[0], "log");
+        assert_eq!(rank("lp", command_names())[0], "log-path");
+        assert_eq!(rank("b", ["beacons", "b", "b"]), ["b", "beacons"]);
+        assert!(rank("zzz", command_names()).is_empty());
+    }
+
+    #[test]
+    fn commands() {
+        assert!(matches!(
+            parse("connect"),
+            Ok(Command::Action(Action::Connect, None))
+        ));
+        assert!(matches!(
+            parse("  conn  "),
+            Ok(Command::Action(Action::Connect, None))
+        ));
+        assert!(matches!(
+            parse("remove AA:BB"),
+            Ok(Command::Action(Action::Remove, Some(q))) if q == "AA:BB"
+        ));
+        assert!(matches!(
+            parse("find Sony WH-1000XM4"),
+            Ok(Command::Action(Action::Find, Some(q))) if q == "Sony WH-1000XM4"
+        ));
+        // Only actions on a device take one
+        assert!(matches!(
+            parse("quit now"),
+            Ok(Command::Action(Action::Quit, None))
+        ));
+        // An exact name wins over the longer ones it starts
+        assert!(matches!(
+            parse("log"),
+            Ok(Command::Action(Action::Log, None))
+        ));
+
+        assert!(matches!(
+            parse("sort sig"),
+            Ok(Command::Sort(SortOrder::Signal))
+        ));
+        assert!(matches!(
+            parse("so name"),
+            Ok(Command::Sort(SortOrder::Name))
+        ));
+        assert!(matches!(
+            parse("adapter hci1"),
+            Ok(Command::Adapter(a)) if a == "hci1"
+        ));
+    }
+
+    #[test]
+    fn typos_are_errors() {
+        assert_eq!(
+            parse("c x").err().unwrap(),
+            "Ambiguous command `c`, could be connect, copy"
+        );
+        assert_eq!(parse("rmove").err().unwrap(), "Unknown command `rmove`");
+        assert_eq!(
+            parse("sort sgnal").err().unwrap(),
+            "Unknown sort order `sgnal`"
+        );
+        assert!(parse("").is_err());
+        assert!(parse("sort").is_err());
+        assert!(parse("adapter").is_err());
+    }
+
+    #[test]
+    fn filter_commands() {
+        let Ok(Command::Filter(filters)) = parse("filter connected rssi>-70 name=Sony WH") else {
+            panic!("expected a filter");
+        };
+
+        assert_eq!(
+            filters,
+            [
+                DeviceFilter::Connected,
+                DeviceFilter::Rssi(Comparison::Greater, -70),
+                DeviceFilter::Name("sony wh".to_string()),
+            ]
+        );
+
+        assert!(matches!(parse("filter"), Ok(Command::Filter(f)) if f.is_empty()));
+        assert!(parse("filter loud").is_err());
+    }
+
+    #[test]
+    fn filters() {
+        let filter = |s: &str| s.parse::<DeviceFilter>();
+
+        assert_eq!(filter("Paired"), Ok(DeviceFilter::Paired));
+        assert_eq!(
+            filter("rssi>=-70"),
+            Ok(DeviceFilter::Rssi(Comparison::GreaterOrEqual, -70))
+        );
+        assert_eq!(
+            filter("battery<=20%"),
+            Ok(DeviceFilter::Battery(Comparison::LessOrEqual, 20))
+        );
+        assert_eq!(
+            filter("battery=50"),
+            Ok(DeviceFilter::Battery(Comparison::Equal, 50))
+        );
+        assert_eq!(
+            filter("NAME=Sony"),
+            Ok(DeviceFilter::Name("sony".to_string()))
+        );
+
+        assert!(filter("name>sony").is_err());
+        assert!(filter("volume>3").is_err());
+        assert!(filter("rssi>loud").is_err());
+        assert!(filter("battery<300").is_err());
+        assert!(filter("rssi").is_err());
+
+        for s in ["connected", "name=sony", "rssi<-80", "battery>=50"] {
+            assert_eq!(filter(s).unwrap().to_string(), s);
+        }
+    }
+
+    const TARGETS: [(&str, &str); 4] = [
+        ("aa:bb:cc:dd:ee:01", "Sony WH-1000XM4"),
+        ("aa:bb:cc:dd:ee:02", "Sony SRS-XB13"),
+        ("11:22:33:44:55:66", "Pixel Buds"),
+        ("12:34:56:78:9a:bc", "12:34:56:78:9a:bc"),
+    ];
+
+    #[test]
+    fn targets_by_address() {
+        assert_eq!(find_target("AA:BB:CC:DD:EE:02", &TARGETS, true), Ok(1));
+        assert_eq!(find_target("11:22", &TARGETS, true), Ok(2));
+        assert_eq!(find_target("12:3", &TARGETS, true), Ok(3));
+        assert_eq!(
+            find_target("AA:bb", &TARGETS, false).unwrap_err(),
+            "`AA:bb` starts several addresses: Sony WH-1000XM4, Sony SRS-XB13"
+        );
+    }
+
+    #[test]
+    fn targets_by_name() {
+        assert_eq!(find_target("pixel", &TARGETS, true), Ok(2));
+        assert_eq!(find_target("wh1000", &TARGETS, true), Ok(0));
+        assert_eq!(
+            find_target("walkman", &TARGETS, false).unwrap_err(),
+            "No device matches `walkman`"
+        );
+
+        // A guess is fine for connecting, but not for removing
+        assert!(find_target("sony", &TARGETS, false).is_ok());
+        assert_eq!(
+            find_target("sony", &TARGETS, true).unwrap_err(),
+            "`sony` could be Sony WH-1000XM4, Sony SRS-XB13, give more of the name or the address"
+        );
+    }
+}
@@ -0,0 +1,471 @@
+//! Keep a record of device and operation events, so they can be looked back on after
+//! banners have expired.
+
+use std::{
+    collections::VecDeque,
+    fs,
+    io::Write,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{anyhow, Result};
+
+use crate::xdg::state_dir;
+
+use super::BTDevice;
+
+const MAX_ENTRIES: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Info => "INFO",
+            Severity::Success => "OK",
+            Severity::Warning => "WARN",
+            Severity::Error => "ERROR",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub time: SystemTime,
+    pub severity: Severity,
+    /// Address and name of the device the entry is about.
+    pub device: Option<(String, String)>,
+    pub message: String,
+}
+
+impl LogEntry {
+    pub fn matches(&self, min_severity: Severity, device: Option<&str>) -> bool {
+        self.severity >= min_severity
+            && device.is_none_or(|addr| self.device.as_ref().is_some_and(|(a, _)| a == addr))
+    }
+}
+
+/// How the log pane is filtered and scrolled.
+#[derive(Debug, Clone)]
+pub struct LogView {
+    /// Lines scrolled up from the newest entry.
+    pub scroll: usize,
+    pub min_severity: Severity,
+    /// Address of the only device to show entries for.
+    pub device: Option<String>,
+}
+
+impl Default for LogView {
+    fn default() -> Self {
+        LogView {
+            scroll: 0,
+            min_severity: Severity::Info,
+            device: None,
+        }
+    }
+}
+
+impl LogView {
+    pub fn cycle_severity(&mut self) {
+        self.min_severity = match self.min_severity {
+            Severity::Info => Severity::Success,
+            Severity::Success => Severity::Warning,
+            Severity::Warning => Severity::Error,
+            Severity::Error => Severity::Info,
+        };
+        self.scroll = 0;
+    }
+
+    /// Step the device filter through every device in the log, then back to all of them.
+    pub fn cycle_device(&mut self, log: &EventLog) {
+        let devices = log.devices();
+        let next = match &self.device {
+            None => 0,
+            Some(addr) => devices
+                .iter()
+                .position(|(a, _)| a == addr)
+                .map_or(devices.len(), |i| i + 1),
+        };
+
+        self.device = devices.get(next).map(|(a, _)| a.clone());
+        self.scroll = 0;
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct EventLog {
+    entries: VecDeque<LogEntry>,
+}
+
+/// The properties of a device worth logging changes to.
+struct DeviceState<'a> {
+    address: &'a str,
+    name: &'a str,
+    connected: bool,
+    paired: bool,
+    battery: Option<u8>,
+    icon_name: &'a str,
+}
+
+impl<'a> From<&'a BTDevice> for DeviceState<'a> {
+    fn from(device: &'a BTDevice) -> Self {
+        DeviceState {
+            address: &device.address,
+            name: &device.name,
+            connected: device.connected,
+            paired: device.paired,
+            battery: device.battery_level(),
+            icon_name: &device.icon_name,
+        }
+    }
+}
+
+impl EventLog {
+    pub fn push(&mut self, severity: Severity, device: Option<&BTDevice>, message: String) {
+        self.push_entry(
+            severity,
+            device.map(|d| (d.address.clone(), d.name.clone())),
+            message,
+        );
+    }
+
+    fn push_entry(
+        &mut self,
+        severity: Severity,
+        device: Option<(String, String)>,
+        message: String,
+    ) {
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(LogEntry {
+            time: SystemTime::now(),
+            severity,
+            device,
+            message,
+        });
+    }
+
+    /// Log the changes between the last known state of a device and an update to it.
+    ///
+    /// RSSI and advertisement data change with nearly every advertisement, so they're
+    /// left to the history and inspect views.
+    pub fn push_changes(&mut self, previous: &BTDevice, device: &BTDevice) {
+        self.push_state_changes(previous.into(), device.into());
+    }
+
+    fn push_state_changes(&mut self, previous: DeviceState, device: DeviceState) {
+        let yes_no = |b: bool| if b { "yes" } else { "no" };
+        let battery = |b: Option<u8>| b.map_or("unknown".to_string(), |b| format!("{}%", b));
+
+        let mut changes = vec![];
+
+        if previous.name != device.name {
+            changes.push(format!("name {} -> {}", previous.name, device.name));
+        }
+        if previous.connected != device.connected {
+            changes.push(format!(
+                "connected {} -> {}",
+                yes_no(previous.connected),
+                yes_no(device.connected)
+            ));
+        }
+        if previous.paired != device.paired {
+            changes.push(format!(
+                "paired {} -> {}",
+                yes_no(previous.paired),
+                yes_no(device.paired)
+            ));
+        }
+        if previous.battery != device.battery {
+            changes.push(format!(
+                "battery {} -> {}",
+                battery(previous.battery),
+                battery(device.battery)
+            ));
+        }
+        if previous.icon_name != device.icon_name {
+            changes.push(format!(
+                "icon {} -> {}",
+                previous.icon_name, device.icon_name
+            ));
+        }
+
+        if !changes.is_empty() {
+            self.push_entry(
+                Severity::Info,
+                Some((device.address.to_string(), device.name.to_string())),
+                changes.join(", "),
+            );
+        }
+    }
+
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &LogEntry> {
+        self.entries.iter()
+    }
+
+    /// Devices mentioned in the log, in the order they first appear.
+    pub fn devices(&self) -> Vec<(String, String)> {
+        let mut devices: Vec<(String, String)> = vec![];
+
+        for (address, name) in self.entries.iter().filter_map(|e| e.device.as_ref()) {
+            if !devices.iter().any(|(a, _)| a == address) {
+                devices.push((address.clone(), name.clone()));
+            }
+        }
+
+        devices
+    }
+
+    /// Write the entries matching the filter to a new file in the state directory, and
+    /// return its path.
+    pub fn save(&self, min_severity: Severity, device: Option<&str>) -> Result<PathBuf> {
+        let dir = state_dir().ok_or_else(|| anyhow!("Couldn't find a state directory"))?;
+        fs::create_dir_all(&dir)?;
+
+        let path = dir.join(format!(
+            "events-{}.log",
+            format_timestamp(SystemTime::now()).replace([' ', ':'], "-")
+        ));
+
+        let mut file = fs::File::create(&path)?;
+
+        for entry in self
+            .entries
+            .iter()
+            .filter(|e| e.matches(min_severity, device))
+        {
+            writeln!(file, "{}", format_entry(entry, true))?;
+        }
+
+        Ok(path)
+    }
+}
+
+/// Format an entry on one line, with the date as well as time when `full` is set.
+pub fn format_entry(entry: &LogEntry, full: bool) -> String {
+    let time = format_timestamp(entry.time);
+    let time = if full { &time } else { &time[11..] };
+
+    match &entry.device {
+        Some((address, name)) => format!(
+            "{} {:<5} {} ({}): {}",
+            time,
+            entry.severity.name(),
+            name,
+            address,
+            entry.message
+        ),
+        None => format!("{} {:<5} {}", time, entry.severity.name(), entry.message),
+    }
+}
+
+/// Format a time as local `YYYY-MM-DD HH:MM:SS`.
+pub fn format_timestamp(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()) as libc::time_t;
+
+    // SAFETY: localtime_r and gmtime_r only write to the tm we hand them
+    let tm = unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+
+        // Fall back to UTC when the local time can't be worked out
+        if libc::localtime_r(&secs, &mut tm).is_null() {
+            libc::gmtime_r(&secs, &mut tm);
+        }
+
+        tm
+    };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn state<'a>(name: &'a str, connected: bool, battery: Option<u8>) -> DeviceState<'a> {
+        DeviceState {
+            address: "aa:bb:cc:dd:ee:ff",
+            name,
+            connected,
+            paired: true,
+            battery,
+            icon_name: "audio-headphones",
+        }
+    }
+
+    fn device(address: &str, name: &str) -> Option<(String, String)> {
+        Some((address.to_string(), name.to_string()))
+    }
+
+    fn entry(severity: Severity, address: Option<&str>) -> LogEntry {
+        LogEntry {
+            time: SystemTime::now(),
+            severity,
+            device: address.and_then(|a| device(a, "Buds")),
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn changes() {
+        let mut log = EventLog::default();
+
+        log.push_state_changes(state("Buds", false, None), state("Buds", false, None));
+        assert_eq!(log.entries().count(), 0);
+
+        log.push_state_changes(
+            state("Buds", false, Some(80)),
+            state("Buds Pro", true, None),
+        );
+
+        let entry = log.entries().next().unwrap();
+        assert_eq!(entry.severity, Severity::Info);
+        assert_eq!(entry.device, device("aa:bb:cc:dd:ee:ff", "Buds Pro"));
+        assert_eq!(
+            entry.message,
+            "name Buds -> Buds Pro, connected no -> yes, battery 80% -> unknown"
+        );
+    }
+
+    #[test]
+    fn matches() {
+        let warning = entry(Severity::Warning, Some("aa:bb:cc:dd:ee:ff"));
+
+        assert!(warning.matches(Severity::Info, None));
+        assert!(warning.matches(Severity::Warning, None));
+        assert!(!warning.matches(Severity::Error, None));
+        assert!(warning.matches(Severity::Info, Some("aa:bb:cc:dd:ee:ff")));
+        assert!(!warning.matches(Severity::Info, Some("11:22:33:44:55:66")));
+
+        // Entries about no device are hidden when filtering by one
+        let general = entry(Severity::Error, None);
+        assert!(general.matches(Severity::Info, None));
+        assert!(!general.matches(Severity::Info, Some("aa:bb:cc:dd:ee:ff")));
+    }
+
+    #[test]
+    fn cycle_severity() {
+        let mut view = LogView {
+            scroll: 4,
+            ..LogView::default()
+        };
+
+        let mut seen = vec![];
+        for _ in 0..4 {
+            view.cycle_severity();
+            seen.push(view.min_severity);
+        }
+
+        assert_eq!(
+            seen,
+            [
+                Severity::Success,
+                Severity::Warning,
+                Severity::Error,
+                Severity::Info
+            ]
+        );
+        assert_eq!(view.scroll, 0);
+    }
+
+    #[test]
+    fn cycle_device() {
+        let mut log = EventLog::default();
+        log.push_entry(Severity::Info, None, "Scanning".to_string());
+        log.push_entry(
+            Severity::Info,
+            device("aa:aa:aa:aa:aa:aa", "A"),
+            String::new(),
+        );
+        log.push_entry(
+            Severity::Info,
+            device("bb:bb:bb:bb:bb:bb", "B"),
+            String::new(),
+        );
+        log.push_entry(
+            Severity::Info,
+            device("aa:aa:aa:aa:aa:aa", "A"),
+            String::new(),
+        );
+
+        let mut view = LogView::default();
+        let mut seen = vec![];
+        for _ in 0..3 {
+            view.cycle_device(&log);
+            seen.push(view.device.clone());
+        }
+
+        assert_eq!(
+            seen,
+            [
+                Some("aa:aa:aa:aa:aa:aa".to_string()),
+                Some("bb:bb:bb:bb:bb:bb".to_string()),
+                None
+            ]
+        );
+
+        // A device no longer in the log goes back to all of them
+        view.device = Some("cc:cc:cc:cc:cc:cc".to_string());
+        view.cycle_device(&log);
+        assert_eq!(view.device, None);
+
+        view.cycle_device(&EventLog::default());
+        assert_eq!(view.device, None);
+    }
+
+    #[test]
+    fn format() {
+        let mut entry = entry(Severity::Warning, Some("aa:bb:cc:dd:ee:ff"));
+        entry.message = "Connection failed".to_string();
+        let time = format_timestamp(entry.time);
+
+        assert_eq!(
+            format_entry(&entry, true),
+            format!("{} WARN  Buds (aa:bb:cc:dd:ee:ff): Connection failed", time)
+        );
+        assert_eq!(
+            format_entry(&entry, false),
+            format!(
+                "{} WARN  Buds (aa:bb:cc:dd:ee:ff): Connection failed",
+                &time[11..]
+            )
+        );
+
+        entry.device = None;
+        entry.severity = Severity::Success;
+        assert_eq!(
+            format_entry(&entry, false),
+            format!("{} OK    Connection failed", &time[11..])
+        );
+    }
+
+    #[test]
+    fn timestamp() {
+        // 2001-09-09 01:46:40 UTC, a day either side of it in any time zone
+        let time = format_timestamp(UNIX_EPOCH + Duration::from_secs(1_000_000_000));
+
+        assert!(time.starts_with("2001-09-0"), "{}", time);
+        assert_eq!(time.len(), 19);
+        assert_eq!(time.matches(':').count(), 2);
+        assert!(time.ends_with(":40"));
+    }
+}
@@ -0,0 +1,121 @@
+//! Follow the signal strength of a single device, to help walk towards it.
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use super::{
+    history::{Ema, RssiReadings, RSSI_CEILING, RSSI_FLOOR, RSSI_SMOOTHING},
+    BTDevice,
+};
+
+/// Number of one second samples kept for the history.
+const HISTORY_LEN: usize = 60;
+
+/// Samples to look back over when deciding the trend.
+const TREND_WINDOW: usize = 5;
+
+/// Change in dBm over the trend window treated as movement rather than noise.
+const TREND_THRESHOLD: f64 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Hotter,
+    Colder,
+    Steady,
+}
+
+#[derive(Debug, Clone)]
+pub struct Finder {
+    pub address: String,
+    pub name: String,
+    /// Smoothed RSSI.
+    pub rssi: Ema,
+    /// Smoothed RSSI sampled once a second, oldest first.
+    pub history: VecDeque<f64>,
+    pub bell: bool,
+    readings: RssiReadings,
+    last_sample: Instant,
+    last_bell: Instant,
+}
+
+impl Finder {
+    pub fn new(device: &BTDevice) -> Self {
+        let mut finder = Finder {
+            address: device.address.clone(),
+            name: device.name.clone(),
+            rssi: Ema::new(RSSI_SMOOTHING),
+            history: VecDeque::with_capacity(HISTORY_LEN),
+            bell: false,
+            readings: RssiReadings::default(),
+            last_sample: Instant::now(),
+            last_bell: Instant::now(),
+        };
+
+        finder.update(device);
+        finder
+    }
+
+    /// Fold in a new reading if `device` is the one being found.
+    pub fn update(&mut self, device: &BTDevice) {
+        if device.address != self.address {
+            return;
+        }
+
+        if let Some(rssi) = self.readings.fresh(device.rssi) {
+            self.rssi.update(rssi as f64);
+        }
+    }
+
+    /// Advance the clock. Returns true when the bell is due to ring.
+    pub fn tick(&mut self) -> bool {
+        if self.last_sample.elapsed() >= Duration::from_secs(1) {
+            self.last_sample = Instant::now();
+
+            if let Some(rssi) = self.rssi.value() {
+                if self.history.len() == HISTORY_LEN {
+                    self.history.pop_front();
+                }
+                self.history.push_back(rssi);
+            }
+        }
+
+        match self.bell_interval() {
+            Some(interval) if self.bell && self.last_bell.elapsed() >= interval => {
+                self.last_bell = Instant::now();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Signal strength scaled to `0.0..=1.0`.
+    pub fn strength(&self) -> Option<f64> {
+        self.rssi
+            .value()
+            .map(|r| ((r - RSSI_FLOOR) / (RSSI_CEILING - RSSI_FLOOR)).clamp(0.0, 1.0))
+    }
+
+    /// Compare the latest sample with the one a few seconds earlier.
+    pub fn trend(&self) -> Option<Trend> {
+        let latest = *self.history.back()?;
+        let earlier = *self
+            .history
+            .get(self.history.len().checked_sub(TREND_WINDOW + 1)?)?;
+
+        Some(if latest - earlier > TREND_THRESHOLD {
+            Trend::Hotter
+        } else if earlier - latest > TREND_THRESHOLD {
+            Trend::Colder
+        } else {
+            Trend::Steady
+        })
+    }
+
+    /// Time between bells, from two seconds at the weakest signal down to a quarter second.
+    fn bell_interval(&self) -> Option<Duration> {
+        self.strength()
+            .map(|s| Duration::from_millis((2000.0 - s * 1750.0) as u64))
+    }
+}
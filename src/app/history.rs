@@ -0,0 +1,432 @@
+//! Bounded, smoothed time series of each device's signal strength and battery level.
+
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use super::BTDevice;
+
+/// Weight given to each new RSSI reading when smoothing.
+pub const RSSI_SMOOTHING: f64 = 0.3;
+
+/// RSSI range shown on gauges and charts, weakest to strongest.
+pub const RSSI_FLOOR: f64 = -100.0;
+pub const RSSI_CEILING: f64 = -30.0;
+
+/// Battery levels are reported in whole or ten percent steps, so they're kept as is.
+const BATTERY_SMOOTHING: f64 = 1.0;
+
+const RSSI_SAMPLES: usize = 120;
+const RSSI_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+
+const BATTERY_SAMPLES: usize = 256;
+const BATTERY_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+const BATTERY_RESAMPLE: Duration = Duration::from_secs(5 * 60);
+
+/// Shortest stretch of discharge worth estimating a drain rate from.
+const MIN_DRAIN_SPAN: Duration = Duration::from_secs(10 * 60);
+
+/// Window over which the recent drain rate is compared to the overall one.
+const RECENT_DRAIN_WINDOW: Duration = Duration::from_secs(30 * 60);
+
+/// How many times faster the recent drain has to be to count as speeding up.
+const DRAIN_SPEEDUP: f64 = 2.0;
+
+/// Exponential moving average.
+#[derive(Debug, Clone, Copy)]
+pub struct Ema {
+    alpha: f64,
+    value: Option<f64>,
+}
+
+impl Ema {
+    pub const fn new(alpha: f64) -> Self {
+        Ema { alpha, value: None }
+    }
+
+    pub fn update(&mut self, x: f64) -> f64 {
+        let next = match self.value {
+            Some(prev) => prev + self.alpha * (x - prev),
+            None => x,
+        };
+
+        self.value = Some(next);
+        next
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
+
+/// Picks out new RSSI readings from device updates. Updates carry every property, so the RSSI
+/// only counts when it has changed, and other properties changing don't repeat it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RssiReadings {
+    last: Option<i16>,
+}
+
+impl RssiReadings {
+    /// `rssi` if it's a new reading.
+    pub fn fresh(&mut self, rssi: Option<i16>) -> Option<i16> {
+        let fresh = rssi.filter(|_| rssi != self.last);
+        self.last = rssi;
+        fresh
+    }
+}
+
+/// Smoothed samples, oldest first, dropped once there are too many or they get too old.
+#[derive(Debug, Clone)]
+pub struct Series {
+    samples: VecDeque<(Instant, f64)>,
+    ema: Ema,
+    capacity: usize,
+    max_age: Duration,
+}
+
+impl Series {
+    fn new(alpha: f64, capacity: usize, max_age: Duration) -> Self {
+        Series {
+            samples: VecDeque::with_capacity(capacity),
+            ema: Ema::new(alpha),
+            capacity,
+            max_age,
+        }
+    }
+
+    fn push(&mut self, at: Instant, x: f64) {
+        let value = self.ema.update(x);
+
+        while self.samples.len() >= self.capacity
+            || self
+                .samples
+                .front()
+                .is_some_and(|(t, _)| at.duration_since(*t) > self.max_age)
+        {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back((at, value));
+    }
+
+    pub fn latest(&self) -> Option<f64> {
+        self.ema.value()
+    }
+
+    pub fn samples(&self) -> impl DoubleEndedIterator<Item = &(Instant, f64)> + ExactSizeIterator {
+        self.samples.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DeviceHistory {
+    pub rssi: Series,
+    pub battery: Series,
+    /// When the device last sent an update.
+    pub last_seen: Instant,
+    readings: RssiReadings,
+}
+
+impl Default for DeviceHistory {
+    fn default() -> Self {
+        DeviceHistory {
+            rssi: Series::new(RSSI_SMOOTHING, RSSI_SAMPLES, RSSI_MAX_AGE),
+            battery: Series::new(BATTERY_SMOOTHING, BATTERY_SAMPLES, BATTERY_MAX_AGE),
+            last_seen: Instant::now(),
+            readings: RssiReadings::default(),
+        }
+    }
+}
+
+impl DeviceHistory {
+    /// Record the readings carried by a device update received at `now`.
+    pub fn update(&mut self, device: &BTDevice, now: Instant) {
+        self.record(now, device.rssi, device.battery_level());
+    }
+
+    fn record(&mut self, now: Instant, rssi: Option<i16>, battery: Option<u8>) {
+        self.last_seen = now;
+
+        if let Some(rssi) = self.readings.fresh(rssi) {
+            self.rssi.push(now, rssi as f64);
+        }
+
+        // Battery levels change slowly, keep the points where they move plus an occasional
+        // one to show where they held steady
+        if let Some(battery) = battery {
+            let stale = self
+                .battery
+                .samples()
+                .next_back()
+                .is_none_or(|(t, _)| now.duration_since(*t) >= BATTERY_RESAMPLE);
+
+            if stale || self.battery.latest() != Some(battery as f64) {
+                self.battery.push(now, battery as f64);
+            }
+        }
+    }
+
+    /// Battery drain in percent per hour, fitted over the samples since it last charged.
+    pub fn drain_rate(&self) -> Option<f64> {
+        drain_rate(&self.discharge())
+    }
+
+    /// Estimated time until the battery runs flat at the current drain rate.
+    pub fn time_left(&self) -> Option<Duration> {
+        let rate = self.drain_rate()?;
+        let level = self.battery.latest()?;
+
+        Some(Duration::from_secs_f64(level / rate * 60.0 * 60.0))
+    }
+
+    /// True when the battery has drained much faster over the last half hour than before.
+    pub fn drain_speeding_up(&self) -> bool {
+        let discharge = self.discharge();
+        let Some(&(last, _)) = discharge.last() else {
+            return false;
+        };
+
+        let split =
+            discharge.partition_point(|(t, _)| last.duration_since(*t) > RECENT_DRAIN_WINDOW);
+
+        // Include the last point before the window, so a single step inside it still counts
+        let recent = &discharge[split.saturating_sub(1)..];
+        let earlier = &discharge[..split];
+
+        match (drain_rate(recent), drain_rate(earlier)) {
+            (Some(recent), Some(earlier)) => recent > earlier * DRAIN_SPEEDUP,
+            _ => false,
+        }
+    }
+
+    /// Battery samples since the level last went up.
+    fn discharge(&self) -> Vec<(Instant, f64)> {
+        let samples = self
+            .battery
+            .samples()
+            .copied()
+            .collect::<Vec<(Instant, f64)>>();
+        let start = samples
+            .windows(2)
+            .rposition(|w| w[1].1 > w[0].1)
+            .map_or(0, |i| i + 1);
+
+        samples[start..].to_vec()
+    }
+}
+
+/// Least squares slope of `samples`, as percent lost per hour. `None` unless the samples
+/// cover enough time and are going down.
+fn drain_rate(samples: &[(Instant, f64)]) -> Option<f64> {
+    let (first, _) = samples.first()?;
+    let (last, _) = samples.last()?;
+
+    if last.duration_since(*first) < MIN_DRAIN_SPAN {
+        return None;
+    }
+
+    let points = samples
+        .iter()
+        .map(|(t, v)| (t.duration_since(*first).as_secs_f64() / (60.0 * 60.0), *v))
+        .collect::<Vec<(f64, f64)>>();
+
+    let n = points.len() as f64;
+    let mean_t = points.iter().map(|(t, _)| t).sum::<f64>() / n;
+    let mean_v = points.iter().map(|(_, v)| v).sum::<f64>() / n;
+
+    let covariance = points
+        .iter()
+        .map(|(t, v)| (t - mean_t) * (v - mean_v))
+        .sum::<f64>();
+    let variance = points
+        .iter()
+        .map(|(t, _)| (t - mean_t).powi(2))
+        .sum::<f64>();
+
+    let rate = -covariance / variance;
+
+    (rate > 0.0).then_some(rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(n: u64) -> Duration {
+        Duration::from_secs(n * 60)
+    }
+
+    #[test]
+    fn ema() {
+        let mut ema = Ema::new(0.5);
+        assert_eq!(ema.value(), None);
+
+        // The first reading is taken as is
+        assert_eq!(ema.update(-60.0), -60.0);
+        assert_eq!(ema.update(-80.0), -70.0);
+        assert_eq!(ema.update(-80.0), -75.0);
+        assert_eq!(ema.value(), Some(-75.0));
+
+        let mut unsmoothed = Ema::new(1.0);
+        unsmoothed.update(50.0);
+        assert_eq!(unsmoothed.update(40.0), 40.0);
+    }
+
+    #[test]
+    fn series_capacity() {
+        let start = Instant::now();
+        let mut series = Series::new(1.0, 3, minutes(60));
+
+        for i in 0..5 {
+            series.push(start + minutes(i), i as f64);
+        }
+
+        let values = series.samples().map(|(_, v)| *v).collect::<Vec<f64>>();
+        assert_eq!(values, [2.0, 3.0, 4.0]);
+        assert_eq!(series.latest(), Some(4.0));
+    }
+
+    #[test]
+    fn series_max_age() {
+        let start = Instant::now();
+        let mut series = Series::new(1.0, 100, minutes(10));
+
+        series.push(start, 1.0);
+        series.push(start + minutes(5), 2.0);
+        series.push(start + minutes(12), 3.0);
+
+        let times = series.samples().map(|(t, _)| *t).collect::<Vec<Instant>>();
+        assert_eq!(times, [start + minutes(5), start + minutes(12)]);
+    }
+
+    #[test]
+    fn series_smoothing() {
+        let start = Instant::now();
+        let mut series = Series::new(0.5, 10, minutes(10));
+
+        series.push(start, -60.0);
+        series.push(start, -80.0);
+
+        let values = series.samples().map(|(_, v)| *v).collect::<Vec<f64>>();
+        assert_eq!(values, [-60.0, -70.0]);
+    }
+
+    #[test]
+    fn fresh_readings() {
+        let mut readings = RssiReadings::default();
+
+        assert_eq!(readings.fresh(Some(-60)), Some(-60));
+        assert_eq!(readings.fresh(Some(-60)), None);
+        assert_eq!(readings.fresh(None), None);
+        assert_eq!(readings.fresh(Some(-60)), Some(-60));
+        assert_eq!(readings.fresh(Some(-61)), Some(-61));
+    }
+
+    #[test]
+    fn only_new_rssi_is_sampled() {
+        let start = Instant::now();
+        let mut history = DeviceHistory::default();
+
+        history.record(start, Some(-60), None);
+        history.record(start + minutes(1), Some(-60), None);
+        history.record(start + minutes(2), Some(-70), None);
+
+        assert_eq!(history.rssi.samples().len(), 2);
+        assert_eq!(history.last_seen, start + minutes(2));
+        assert!(history.battery.is_empty());
+    }
+
+    #[test]
+    fn steady_battery_is_resampled() {
+        let start = Instant::now();
+        let mut history = DeviceHistory::default();
+
+        history.record(start, None, Some(80));
+        history.record(start + minutes(1), None, Some(80));
+        assert_eq!(history.battery.samples().len(), 1);
+
+        history.record(start + minutes(2), None, Some(79));
+        assert_eq!(history.battery.samples().len(), 2);
+
+        history.record(start + minutes(7), None, Some(79));
+        assert_eq!(history.battery.samples().len(), 3);
+    }
+
+    /// A history of battery levels given at minutes after the start.
+    fn battery(levels: impl IntoIterator<Item = (u64, u8)>) -> DeviceHistory {
+        let start = Instant::now();
+        let mut history = DeviceHistory::default();
+
+        for (t, level) in levels {
+            history.record(start + minutes(t), None, Some(level));
+        }
+
+        history
+    }
+
+    fn assert_near(a: f64, b: f64) {
+        assert!((a - b).abs() < 0.01, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn flat_battery() {
+        let history = battery((0..=60).step_by(5).map(|t| (t, 80)));
+
+        assert_eq!(history.drain_rate(), None);
+        assert_eq!(history.time_left(), None);
+        assert!(!history.drain_speeding_up());
+    }
+
+    #[test]
+    fn linear_drain() {
+        // 1% every 6 minutes
+        let history = battery((0..=10).map(|i| (i * 6, 80 - i as u8)));
+
+        assert_near(history.drain_rate().unwrap(), 10.0);
+        assert_near(history.time_left().unwrap().as_secs_f64() / 3600.0, 7.0);
+        assert!(!history.drain_speeding_up());
+    }
+
+    #[test]
+    fn charging() {
+        let history = battery((0..=10).map(|i| (i * 6, 50 + i as u8)));
+        assert_eq!(history.drain_rate(), None);
+        assert_eq!(history.time_left(), None);
+
+        // Only the drain since it was last charged counts
+        let history = battery(
+            [(0, 40), (6, 30), (12, 60)]
+                .into_iter()
+                .chain((1..=5).map(|i| (12 + i * 6, 60 - i as u8))),
+        );
+        assert_near(history.drain_rate().unwrap(), 10.0);
+        assert_near(history.time_left().unwrap().as_secs_f64() / 3600.0, 5.5);
+    }
+
+    #[test]
+    fn too_few_samples() {
+        assert_eq!(battery([]).drain_rate(), None);
+        assert_eq!(battery([(0, 80)]).drain_rate(), None);
+        assert_eq!(battery([(0, 80), (5, 79)]).drain_rate(), None);
+        assert_eq!(battery([(0, 80), (5, 79)]).time_left(), None);
+        assert!(!battery([(0, 80), (5, 79)]).drain_speeding_up());
+    }
+
+    #[test]
+    fn drain_speeding_up() {
+        // 1% an hour for three hours, then 1% every 6 minutes
+        let slow = (0..=180).step_by(5).map(|t| (t, 90 - (t / 60) as u8));
+        let fast = (1..=5).map(|i| (180 + i * 6, 87 - i as u8));
+        let history = battery(slow.chain(fast));
+
+        assert!(history.drain_speeding_up());
+
+        let steady = battery((0..=210).step_by(5).map(|t| (t, 90 - (t / 60) as u8)));
+        assert!(!steady.drain_speeding_up());
+    }
+}
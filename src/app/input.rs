@@ -1,70 +1,111 @@
 use futures::StreamExt;
-use std::sync::Arc;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
 
-use crossterm::event::{Event, EventStream, KeyCode};
-use tokio::{sync::mpsc::Sender, task::JoinHandle};
+use crossterm::event::{Event, EventStream, KeyEventKind, MouseButton, MouseEventKind};
+use tokio::{sync::mpsc::Sender, task::JoinHandle, time::Duration};
 
-use super::bluetool::AppEvent;
+use super::{
+    bluetool::AppEvent,
+    keymap::{Action, KeyMatch, KeyPress, Keymap},
+};
 
-pub fn launch_key_listener(event_send_chan: Arc<Sender<AppEvent>>) -> JoinHandle<()> {
+/// How long to wait for the rest of a multi-key binding.
+const SEQUENCE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Listen for keys, sending the actions they're bound to. While `typing` is set every key is
+/// sent as it is for the command line instead. Clicks and the scroll wheel are sent too.
+pub fn launch_key_listener(
+    event_send_chan: Arc<Sender<AppEvent>>,
+    keymap: Keymap,
+    typing: Arc<AtomicBool>,
+) -> JoinHandle<()> {
     let mut event_stream = EventStream::new();
 
     tokio::spawn(async move {
+        let mut pending: Vec<KeyPress> = vec![];
+        let mut last_key = Instant::now();
+
         loop {
-            match event_stream.next().await {
-                Some(Ok(Event::Key(evnt))) => match evnt.code {
-                    // Quit key
-                    KeyCode::Char('q') => {
-                        event_send_chan.send(AppEvent::Exit).await.unwrap();
-                    }
-                    KeyCode::Esc => {
-                        event_send_chan.send(AppEvent::Esc).await.unwrap();
-                    }
-                    KeyCode::Char('j') | KeyCode::Down => {
-                        event_send_chan.send(AppEvent::ScrollDown).await.unwrap();
-                    }
-                    KeyCode::Char('k') | KeyCode::Up => {
-                        event_send_chan.send(AppEvent::ScrollUp).await.unwrap();
-                    }
-                    KeyCode::Char('c') => {
-                        event_send_chan
-                            .send(AppEvent::ConnectRequested)
-                            .await
-                            .unwrap();
-                    }
-                    KeyCode::Char('p') => {
-                        event_send_chan.send(AppEvent::PairRequested).await.unwrap();
-                    }
-                    KeyCode::Char('d') => {
-                        event_send_chan
-                            .send(AppEvent::DisconnectRequested)
-                            .await
-                            .unwrap();
-                    }
-                    KeyCode::Char('r') => {
-                        event_send_chan
-                            .send(AppEvent::RemoveRequested)
-                            .await
-                            .unwrap();
-                    }
-                    KeyCode::Char('h') => {
-                        event_send_chan
-                            .send(AppEvent::ShowHideUnnamed)
-                            .await
-                            .unwrap();
-                    }
-                    KeyCode::Char('i') => {
-                        event_send_chan
-                            .send(AppEvent::InspectCurrent)
-                            .await
-                            .unwrap();
+            let evnt = match event_stream.next().await {
+                Some(Ok(Event::Key(evnt))) => evnt,
+                Some(Ok(Event::Mouse(mouse))) if !typing.load(Ordering::Relaxed) => {
+                    // Mouse capture reports every movement too, only pass on what we use
+                    let event = match mouse.kind {
+                        MouseEventKind::Down(MouseButton::Left) => {
+                            AppEvent::Click(mouse.column, mouse.row)
+                        }
+                        MouseEventKind::ScrollDown => AppEvent::ScrollDown,
+                        MouseEventKind::ScrollUp => AppEvent::ScrollUp,
+                        _ => continue,
+                    };
+
+                    if event_send_chan.send(event).await.is_err() {
+                        break;
                     }
-                    _ => {
-                        event_send_chan.send(AppEvent::Pass).await.unwrap();
+                    continue;
+                }
+                _ => continue,
+            };
+
+            if evnt.kind == KeyEventKind::Release {
+                continue;
+            }
+
+            if typing.load(Ordering::Relaxed) {
+                pending.clear();
+
+                if event_send_chan
+                    .send(AppEvent::CommandKey(evnt))
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+                continue;
+            }
+
+            if last_key.elapsed() > SEQUENCE_TIMEOUT {
+                pending.clear();
+            }
+            last_key = Instant::now();
+
+            pending.push(evnt.into());
+
+            let mut matched = keymap.lookup(&pending);
+
+            // A key which breaks a sequence may still start or be a binding of its own
+            if matches!(matched, KeyMatch::None) && pending.len() > 1 {
+                pending = vec![evnt.into()];
+                matched = keymap.lookup(&pending);
+            }
+
+            let event = match matched {
+                KeyMatch::Action(action) => {
+                    pending.clear();
+
+                    // Don't wait for the app to catch up, or the first keys typed could be lost
+                    if action == Action::Command {
+                        typing.store(true, Ordering::Relaxed);
                     }
-                },
-                _ => {}
+
+                    action.event()
+                }
+                KeyMatch::Pending => continue,
+                KeyMatch::None => {
+                    pending.clear();
+                    AppEvent::Pass
+                }
             };
+
+            if event_send_chan.send(event).await.is_err() {
+                break;
+            }
         }
     })
 }
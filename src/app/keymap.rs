@@ -0,0 +1,708 @@
+//! Bind actions to key sequences, so keys can be remapped from the config.
+
+use std::{collections::BTreeMap, fmt};
+
+use anyhow::{anyhow, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{
+    de::{self, SeqAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+use super::bluetool::{AppEvent, AppMode};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Quit,
+    Back,
+    Down,
+    Up,
+    PageDown,
+    PageUp,
+    Connect,
+    Pair,
+    Disconnect,
+    Remove,
+    ToggleUnnamed,
+    Inspect,
+    Copy,
+    Details,
+    Beacons,
+    Find,
+    ToggleBell,
+    Log,
+    LogDevice,
+    LogSeverity,
+    SaveLog,
+    LogPath,
+    Help,
+    Command,
+}
+
+impl Action {
+    pub const ALL: [Action; 24] = [
+        Action::Quit,
+        Action::Back,
+        Action::Help,
+        Action::Command,
+        Action::LogPath,
+        Action::Down,
+        Action::Up,
+        Action::PageDown,
+        Action::PageUp,
+        Action::Connect,
+        Action::Pair,
+        Action::Disconnect,
+        Action::Remove,
+        Action::Inspect,
+        Action::Find,
+        Action::Copy,
+        Action::ToggleUnnamed,
+        Action::Details,
+        Action::Beacons,
+        Action::Log,
+        Action::ToggleBell,
+        Action::LogDevice,
+        Action::LogSeverity,
+        Action::SaveLog,
+    ];
+
+    /// The action's name as written in the config.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Back => "back",
+            Action::Down => "down",
+            Action::Up => "up",
+            Action::PageDown => "page-down",
+            Action::PageUp => "page-up",
+            Action::Connect => "connect",
+            Action::Pair => "pair",
+            Action::Disconnect => "disconnect",
+            Action::Remove => "remove",
+            Action::ToggleUnnamed => "toggle-unnamed",
+            Action::Inspect => "inspect",
+            Action::Copy => "copy",
+            Action::Details => "details",
+            Action::Beacons => "beacons",
+            Action::Find => "find",
+            Action::ToggleBell => "toggle-bell",
+            Action::Log => "log",
+            Action::LogDevice => "log-device",
+            Action::LogSeverity => "log-severity",
+            Action::SaveLog => "save-log",
+            Action::LogPath => "log-path",
+            Action::Help => "help",
+            Action::Command => "command",
+        }
+    }
+
+    /// What the action does, for the help overlay.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::Back => "Return to the device list",
+            Action::Down => "Select the next device, or scroll down",
+            Action::Up => "Select the previous device, or scroll up",
+            Action::PageDown => "Scroll down a page",
+            Action::PageUp => "Scroll up a page",
+            Action::Connect => "Connect to the selected device",
+            Action::Pair => "Pair with the selected device",
+            Action::Disconnect => "Disconnect from the selected device",
+            Action::Remove => "Remove the selected device",
+            Action::ToggleUnnamed => "Show or hide unnamed devices",
+            Action::Inspect => "Inspect the selected device",
+            Action::Copy => "Copy the selected field to the clipboard",
+            Action::Details => "Show details beside or below the list, or hide them",
+            Action::Beacons => "Show beacons",
+            Action::Find => "Find the selected device",
+            Action::ToggleBell => "Turn the proximity bell on or off",
+            Action::Log => "Show the event log",
+            Action::LogDevice => "Show events for the next device",
+            Action::LogSeverity => "Raise the minimum severity shown",
+            Action::SaveLog => "Save the events shown to a file",
+            Action::LogPath => "Show where the log file is",
+            Action::Help => "Show or hide this help",
+            Action::Command => "Type a command, e.g. connect <device>",
+        }
+    }
+
+    /// Heading the action is listed under in the help overlay.
+    pub fn category(&self) -> &'static str {
+        match self {
+            Action::Quit | Action::Back | Action::Help | Action::Command | Action::LogPath => {
+                "General"
+            }
+            Action::Down | Action::Up | Action::PageDown | Action::PageUp => "Navigation",
+            Action::Connect
+            | Action::Pair
+            | Action::Disconnect
+            | Action::Remove
+            | Action::Inspect
+            | Action::Find => "Device",
+            Action::ToggleUnnamed | Action::Details | Action::Beacons | Action::Log => "View",
+            Action::Copy => "Inspect",
+            Action::ToggleBell => "Find",
+            Action::LogDevice | Action::LogSeverity | Action::SaveLog => "Event log",
+        }
+    }
+
+    /// Whether the action does anything in `mode`.
+    pub fn available(&self, mode: &AppMode) -> bool {
+        match self {
+            Action::Quit | Action::Help | Action::Command | Action::LogPath => true,
+            Action::Back => !matches!(mode, AppMode::Browse),
+            Action::Down | Action::Up => matches!(
+                mode,
+                AppMode::Browse | AppMode::Log | AppMode::Beacons | AppMode::Inspect(_)
+            ),
+            Action::PageDown | Action::PageUp | Action::Copy => {
+                matches!(mode, AppMode::Inspect(_))
+            }
+            Action::Connect
+            | Action::Pair
+            | Action::Disconnect
+            | Action::Remove
+            | Action::Inspect
+            | Action::Find
+            | Action::ToggleUnnamed
+            | Action::Details
+            | Action::Beacons
+            | Action::Log => matches!(mode, AppMode::Browse),
+            Action::ToggleBell => matches!(mode, AppMode::Find),
+            Action::LogDevice | Action::LogSeverity | Action::SaveLog => {
+                matches!(mode, AppMode::Log)
+            }
+        }
+    }
+
+    pub fn event(&self) -> AppEvent {
+        match self {
+            Action::Quit => AppEvent::Exit,
+            Action::Back => AppEvent::Esc,
+            Action::Down => AppEvent::ScrollDown,
+            Action::Up => AppEvent::ScrollUp,
+            Action::PageDown => AppEvent::PageDown,
+            Action::PageUp => AppEvent::PageUp,
+            Action::Connect => AppEvent::ConnectRequested,
+            Action::Pair => AppEvent::PairRequested,
+            Action::Disconnect => AppEvent::DisconnectRequested,
+            Action::Remove => AppEvent::RemoveRequested,
+            Action::ToggleUnnamed => AppEvent::ShowHideUnnamed,
+            Action::Inspect => AppEvent::InspectCurrent,
+            Action::Copy => AppEvent::CopyField,
+            Action::Details => AppEvent::CycleDetails,
+            Action::Beacons => AppEvent::ShowBeacons,
+            Action::Find => AppEvent::FindCurrent,
+            Action::ToggleBell => AppEvent::ToggleBell,
+            Action::Log => AppEvent::ShowLog,
+            Action::LogDevice => AppEvent::CycleLogDevice,
+            Action::LogSeverity => AppEvent::CycleLogSeverity,
+            Action::SaveLog => AppEvent::SaveLog,
+            Action::LogPath => AppEvent::ShowLogPath,
+            Action::Help => AppEvent::ShowHelp,
+            Action::Command => AppEvent::ShowCommand,
+        }
+    }
+}
+
+/// A single key press with its modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyPress {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyPress {
+    const fn new(code: KeyCode) -> Self {
+        KeyPress {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    const fn char(c: char) -> Self {
+        KeyPress::new(KeyCode::Char(c))
+    }
+
+    /// Terminals report shift+tab as `BackTab`, with or without shift set, so keep it as a
+    /// plain `BackTab` however it was written or received.
+    fn normalized(mut self) -> Self {
+        match self.code {
+            KeyCode::BackTab => self.modifiers.remove(KeyModifiers::SHIFT),
+            KeyCode::Tab if self.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.code = KeyCode::BackTab;
+                self.modifiers.remove(KeyModifiers::SHIFT);
+            }
+            _ => {}
+        }
+        self
+    }
+}
+
+impl From<KeyEvent> for KeyPress {
+    fn from(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+
+        // Shift is already part of the character, but not of keys like Tab
+        if !matches!(event.code, KeyCode::Char(_)) {
+            modifiers |= event.modifiers & KeyModifiers::SHIFT;
+        }
+
+        KeyPress {
+            code: event.code,
+            modifiers,
+        }
+        .normalized()
+    }
+}
+
+impl std::str::FromStr for KeyPress {
+    type Err = String;
+
+    /// Parse keys such as `j`, `ctrl+c`, `alt+enter` or `shift+tab`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('+').collect::<Vec<&str>>();
+
+        // A lone `+` or a trailing `++` means the plus key itself
+        let key = match parts.pop() {
+            Some("") if s.ends_with('+') => {
+                parts.pop();
+                "+"
+            }
+            Some(k) => k,
+            None => return Err(format!("empty key in `{}`", s)),
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for m in parts {
+            modifiers |= match m.to_lowercase().as_str() {
+                "ctrl" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier `{}` in `{}`", m, s)),
+            };
+        }
+
+        let code = match key.to_lowercase().as_str() {
+            "esc" | "escape" => KeyCode::Esc,
+            "enter" | "return" => KeyCode::Enter,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            f if f.len() > 1 && f.starts_with('f') => f[1..]
+                .parse::<u8>()
+                .map(KeyCode::F)
+                .map_err(|_| format!("unknown key `{}`", key))?,
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(format!("unknown key `{}`", key)),
+                }
+            }
+        };
+
+        Ok(KeyPress { code, modifiers }.normalized())
+    }
+}
+
+impl fmt::Display for KeyPress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift+")?;
+        }
+
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "SPACE"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Esc => write!(f, "ESC"),
+            KeyCode::Enter => write!(f, "ENTER"),
+            KeyCode::Tab => write!(f, "TAB"),
+            KeyCode::BackTab => write!(f, "shift+TAB"),
+            KeyCode::Backspace => write!(f, "BACKSPACE"),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::PageUp => write!(f, "PGUP"),
+            KeyCode::PageDown => write!(f, "PGDN"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// Keys pressed one after another, such as `g g`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeySequence(pub Vec<KeyPress>);
+
+impl TryFrom<String> for KeySequence {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        let keys = s
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<KeyPress>, String>>()?;
+
+        if keys.is_empty() {
+            return Err("empty key binding".to_string());
+        }
+
+        Ok(KeySequence(keys))
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys = self
+            .0
+            .iter()
+            .map(|k| k.to_string())
+            .collect::<Vec<String>>();
+        write!(f, "{}", keys.join(" "))
+    }
+}
+
+/// Bindings for one action in the config, either a single sequence or a list of them.
+#[derive(Debug, Clone, Default)]
+pub struct Bindings(pub Vec<KeySequence>);
+
+impl<'de> Deserialize<'de> for Bindings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BindingsVisitor;
+
+        impl<'de> Visitor<'de> for BindingsVisitor {
+            type Value = Bindings;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a key binding or a list of them")
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<Bindings, E> {
+                KeySequence::try_from(s.to_string())
+                    .map(|k| Bindings(vec![k]))
+                    .map_err(E::custom)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Bindings, A::Error> {
+                let mut keys = vec![];
+                while let Some(k) = seq.next_element()? {
+                    keys.push(k);
+                }
+                Ok(Bindings(keys))
+            }
+        }
+
+        deserializer.deserialize_any(BindingsVisitor)
+    }
+}
+
+pub enum KeyMatch {
+    /// The keys complete a binding.
+    Action(Action),
+    /// The keys are the start of at least one binding.
+    Pending,
+    None,
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(KeySequence, Action)>,
+}
+
+impl Keymap {
+    /// The default bindings, with any actions given in `overrides` rebound.
+    pub fn new(overrides: &BTreeMap<Action, Bindings>) -> Result<Self> {
+        let mut bindings = DEFAULT_KEYS
+            .iter()
+            .filter(|(_, action)| !overrides.contains_key(action))
+            .map(|(key, action)| (KeySequence(vec![*key]), *action))
+            .collect::<Vec<(KeySequence, Action)>>();
+
+        for (action, keys) in overrides {
+            bindings.extend(keys.0.iter().map(|k| (k.clone(), *action)));
+        }
+
+        // A binding which starts another would always fire first, so the longer one never could
+        for (i, (keys, action)) in bindings.iter().enumerate() {
+            for (other_keys, other) in &bindings[i + 1..] {
+                if keys == other_keys {
+                    return Err(anyhow!(
+                        "`{}` is bound to both {} and {}",
+                        keys,
+                        action.name(),
+                        other.name()
+                    ));
+                }
+
+                let (short, long) = if keys.0.len() < other_keys.0.len() {
+                    ((keys, action), (other_keys, other))
+                } else {
+                    ((other_keys, other), (keys, action))
+                };
+
+                if long.0 .0.starts_with(&short.0 .0) {
+                    return Err(anyhow!(
+                        "`{}` ({}) is the start of `{}` ({}), which could never be pressed",
+                        short.0,
+                        short.1.name(),
+                        long.0,
+                        long.1.name()
+                    ));
+                }
+            }
+        }
+
+        Ok(Keymap { bindings })
+    }
+
+    pub fn lookup(&self, keys: &[KeyPress]) -> KeyMatch {
+        let mut pending = false;
+
+        for (sequence, action) in &self.bindings {
+            if sequence.0 == keys {
+                return KeyMatch::Action(*action);
+            }
+
+            pending |= sequence.0.starts_with(keys);
+        }
+
+        if pending {
+            KeyMatch::Pending
+        } else {
+            KeyMatch::None
+        }
+    }
+
+    /// Every key sequence bound to `action`.
+    pub fn keys_for(&self, action: Action) -> impl Iterator<Item = &KeySequence> {
+        self.bindings
+            .iter()
+            .filter(move |(_, a)| *a == action)
+            .map(|(k, _)| k)
+    }
+
+    /// The first key sequence bound to `action`, for showing in the controls bar.
+    pub fn key_for(&self, action: Action) -> Option<&KeySequence> {
+        self.keys_for(action).next()
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::new(&BTreeMap::new()).expect("default key bindings conflict")
+    }
+}
+
+static DEFAULT_KEYS: [(KeyPress, Action); 27] = [
+    (KeyPress::char('q'), Action::Quit),
+    (
+        KeyPress {
+            code: KeyCode::Char('c'),
+            modifiers: KeyModifiers::CONTROL,
+        },
+        Action::Quit,
+    ),
+    (KeyPress::new(KeyCode::Esc), Action::Back),
+    (KeyPress::char('j'), Action::Down),
+    (KeyPress::new(KeyCode::Down), Action::Down),
+    (KeyPress::char('k'), Action::Up),
+    (KeyPress::new(KeyCode::Up), Action::Up),
+    (KeyPress::new(KeyCode::PageDown), Action::PageDown),
+    (KeyPress::new(KeyCode::PageUp), Action::PageUp),
+    (KeyPress::char('c'), Action::Connect),
+    (KeyPress::char('p'), Action::Pair),
+    (KeyPress::char('d'), Action::Disconnect),
+    (KeyPress::char('r'), Action::Remove),
+    (KeyPress::char('u'), Action::ToggleUnnamed),
+    (KeyPress::char('i'), Action::Inspect),
+    (KeyPress::char('v'), Action::Details),
+    (KeyPress::char('y'), Action::Copy),
+    (KeyPress::char('b'), Action::Beacons),
+    (KeyPress::char('f'), Action::Find),
+    (KeyPress::char('a'), Action::ToggleBell),
+    (KeyPress::char('l'), Action::Log),
+    (KeyPress::new(KeyCode::Tab), Action::LogDevice),
+    (KeyPress::char('e'), Action::LogSeverity),
+    (KeyPress::char('s'), Action::SaveLog),
+    (KeyPress::char('L'), Action::LogPath),
+    (KeyPress::char('?'), Action::Help),
+    (KeyPress::char(':'), Action::Command),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(s: &str) -> KeySequence {
+        KeySequence::try_from(s.to_string()).unwrap()
+    }
+
+    fn overrides(bindings: &[(Action, &str)]) -> BTreeMap<Action, Bindings> {
+        let mut map = BTreeMap::<Action, Bindings>::new();
+        for (action, k) in bindings {
+            map.entry(*action).or_default().0.push(keys(k));
+        }
+        map
+    }
+
+    #[test]
+    fn parse_keys() {
+        assert_eq!("j".parse(), Ok(KeyPress::char('j')));
+        assert_eq!("J".parse(), Ok(KeyPress::char('J')));
+        assert_eq!("space".parse(), Ok(KeyPress::char(' ')));
+        assert_eq!("+".parse(), Ok(KeyPress::char('+')));
+        assert_eq!(
+            "ctrl++".parse(),
+            Ok(KeyPress {
+                code: KeyCode::Char('+'),
+                modifiers: KeyModifiers::CONTROL,
+            })
+        );
+        assert_eq!(
+            "Ctrl+Alt+Enter".parse(),
+            Ok(KeyPress {
+                code: KeyCode::Enter,
+                modifiers: KeyModifiers::CONTROL | KeyModifiers::ALT,
+            })
+        );
+        assert_eq!("f5".parse(), Ok(KeyPress::new(KeyCode::F(5))));
+        assert_eq!("pagedown".parse(), Ok(KeyPress::new(KeyCode::PageDown)));
+
+        assert!("hyper+j".parse::<KeyPress>().is_err());
+        assert!("jk".parse::<KeyPress>().is_err());
+        assert!("fx".parse::<KeyPress>().is_err());
+        assert!(KeySequence::try_from("  ".to_string()).is_err());
+
+        assert_eq!(keys("g g").0, [KeyPress::char('g'), KeyPress::char('g')]);
+    }
+
+    #[test]
+    fn shift_tab() {
+        let backtab = KeyPress::new(KeyCode::BackTab);
+
+        assert_eq!("shift+tab".parse(), Ok(backtab));
+        assert_eq!("backtab".parse(), Ok(backtab));
+        assert_eq!("shift+backtab".parse(), Ok(backtab));
+
+        // crossterm sets shift along with BackTab
+        let event = KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert_eq!(KeyPress::from(event), backtab);
+        let event = KeyEvent::new(KeyCode::Tab, KeyModifiers::SHIFT);
+        assert_eq!(KeyPress::from(event), backtab);
+
+        let keymap = Keymap::new(&overrides(&[(Action::LogDevice, "shift+tab")])).unwrap();
+        let event = KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert!(matches!(
+            keymap.lookup(&[event.into()]),
+            KeyMatch::Action(Action::LogDevice)
+        ));
+    }
+
+    #[test]
+    fn shift_is_part_of_characters() {
+        let event = KeyEvent::new(KeyCode::Char('L'), KeyModifiers::SHIFT);
+        assert_eq!(KeyPress::from(event), KeyPress::char('L'));
+    }
+
+    #[test]
+    fn chords() {
+        let keymap =
+            Keymap::new(&overrides(&[(Action::Quit, "g q"), (Action::Help, "g h")])).unwrap();
+
+        assert!(matches!(
+            keymap.lookup(&[KeyPress::char('g')]),
+            KeyMatch::Pending
+        ));
+        assert!(matches!(
+            keymap.lookup(&keys("g q").0),
+            KeyMatch::Action(Action::Quit)
+        ));
+        assert!(matches!(
+            keymap.lookup(&keys("g h").0),
+            KeyMatch::Action(Action::Help)
+        ));
+        assert!(matches!(keymap.lookup(&keys("g x").0), KeyMatch::None));
+
+        // The defaults for rebound actions are gone
+        assert!(matches!(
+            keymap.lookup(&[KeyPress::char('q')]),
+            KeyMatch::None
+        ));
+        assert!(matches!(
+            keymap.lookup(&[KeyPress::char('c')]),
+            KeyMatch::Action(Action::Connect)
+        ));
+    }
+
+    #[test]
+    fn conflicts() {
+        let err = Keymap::new(&overrides(&[(Action::Help, "c")])).unwrap_err();
+        assert_eq!(err.to_string(), "`c` is bound to both connect and help");
+
+        let err = Keymap::new(&overrides(&[(Action::Help, "c c")])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`c` (connect) is the start of `c c` (help), which could never be pressed"
+        );
+
+        let err =
+            Keymap::new(&overrides(&[(Action::Quit, "g"), (Action::Help, "g g")])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`g` (quit) is the start of `g g` (help), which could never be pressed"
+        );
+
+        // Rebinding connect frees up `c`
+        assert!(Keymap::new(&overrides(&[
+            (Action::Connect, "enter"),
+            (Action::Help, "c c"),
+        ]))
+        .is_ok());
+    }
+
+    #[test]
+    fn key_for_is_stable() {
+        let bindings = overrides(&[
+            (Action::Quit, "x"),
+            (Action::Help, "h"),
+            (Action::Quit, "ctrl+q"),
+        ]);
+
+        for _ in 0..10 {
+            let keymap = Keymap::new(&bindings).unwrap();
+            assert_eq!(keymap.key_for(Action::Quit), Some(&keys("x")));
+            assert_eq!(
+                keymap.keys_for(Action::Quit).collect::<Vec<_>>(),
+                [&keys("x"), &keys("ctrl+q")]
+            );
+        }
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(keys("ctrl+c").to_string(), "ctrl+c");
+        assert_eq!(keys("g space").to_string(), "g SPACE");
+        assert_eq!(keys("shift+tab").to_string(), "shift+TAB");
+    }
+}
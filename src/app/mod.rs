@@ -1,6 +1,23 @@
+mod alerts;
+mod beacons;
 mod bluetool;
 mod bluetooth;
+mod clipboard;
+mod command;
+mod eventlog;
+mod finder;
+mod history;
 mod input;
+mod keymap;
+mod notify;
+mod ticker;
 
+pub use beacons::{Beacon, BeaconId, BeaconTracker};
 pub use bluetool::{AppMode, Banner, BannerType, BluetoolApp};
 pub use bluetooth::BTDevice;
+pub use command::{CommandLine, DeviceFilter};
+pub use eventlog::{format_entry, EventLog, LogView, Severity};
+pub use finder::{Finder, Trend};
+pub use history::{DeviceHistory, Series, RSSI_CEILING, RSSI_FLOOR};
+pub use keymap::{Action, Bindings, Keymap};
+pub use notify::NotifyEvent;
@@ -0,0 +1,303 @@
+//! Send desktop notifications through the freedesktop notification service.
+
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use dbus::{
+    arg::{PropMap, RefArg, Variant},
+    channel::Channel,
+    nonblock::{Proxy, SyncConnection},
+};
+use log::debug;
+use serde::Deserialize;
+
+use crate::config::NotifyConfig;
+
+const NOTIFICATIONS_NAME: &str = "org.freedesktop.Notifications";
+const NOTIFICATIONS_PATH: &str = "/org/freedesktop/Notifications";
+
+#[derive(Debug, Clone, Copy)]
+pub enum Urgency {
+    Normal = 1,
+    Critical = 2,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum NotifyEvent {
+    #[serde(rename = "connect")]
+    Connected,
+    #[serde(rename = "disconnect")]
+    Disconnected,
+    #[serde(rename = "pair")]
+    Paired,
+    #[serde(rename = "fail")]
+    Failed,
+    #[serde(rename = "battery")]
+    LowBattery,
+}
+
+impl NotifyEvent {
+    pub const ALL: [NotifyEvent; 5] = [
+        NotifyEvent::Connected,
+        NotifyEvent::Disconnected,
+        NotifyEvent::Paired,
+        NotifyEvent::Failed,
+        NotifyEvent::LowBattery,
+    ];
+
+    fn summary(&self) -> &'static str {
+        match self {
+            NotifyEvent::Connected => "Device connected",
+            NotifyEvent::Disconnected => "Device disconnected",
+            NotifyEvent::Paired => "Device paired",
+            NotifyEvent::Failed => "Bluetooth action failed",
+            NotifyEvent::LowBattery => "Low battery",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Notifier {
+    conn: Arc<SyncConnection>,
+    events: HashSet<NotifyEvent>,
+}
+
+impl Notifier {
+    /// Connect to the bus given in `config`, or the session bus.
+    pub fn connect(config: &NotifyConfig) -> Result<Self> {
+        let (resource, conn) = match &config.bus {
+            Some(address) => {
+                let mut channel = Channel::open_private(address)?;
+                channel.register()?;
+                dbus_tokio::connection::from_channel(channel)?
+            }
+            None => dbus_tokio::connection::new_session_sync()?,
+        };
+
+        tokio::spawn(async move {
+            let _ = resource.await;
+        });
+
+        Ok(Notifier {
+            conn,
+            events: config.events.clone(),
+        })
+    }
+
+    pub async fn send(&self, summary: &str, body: &str, urgency: Urgency) -> Result<()> {
+        let proxy = Proxy::new(
+            NOTIFICATIONS_NAME,
+            NOTIFICATIONS_PATH,
+            Duration::from_secs(5),
+            self.conn.clone(),
+        );
+
+        let mut hints = PropMap::new();
+        hints.insert(
+            "urgency".to_string(),
+            Variant(Box::new(urgency as u8) as Box<dyn RefArg>),
+        );
+
+        let _: (u32,) = proxy
+            .method_call(
+                NOTIFICATIONS_NAME,
+                "Notify",
+                (
+                    "bluetool",
+                    0u32,
+                    "bluetooth",
+                    summary,
+                    body,
+                    Vec::<&str>::new(),
+                    hints,
+                    -1i32,
+                ),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Send a notification for `event` in the background if it's enabled, ignoring
+    /// failures such as no notification daemon running.
+    pub fn notify(&self, event: NotifyEvent, body: String, urgency: Urgency) {
+        if !self.events.contains(&event) {
+            return;
+        }
+
+        let notifier = self.clone();
+
+        tokio::spawn(async move {
+            if let Err(e) = notifier.send(event.summary(), &body, urgency).await {
+                debug!("Failed to send notification: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        process::{Child, Command, Stdio},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Mutex,
+        },
+        thread,
+    };
+
+    use dbus::{
+        blocking::Connection,
+        channel::{MatchingReceiver, Sender},
+        message::MatchRule,
+    };
+
+    use super::*;
+
+    /// A private bus, killed when dropped.
+    struct Bus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl Bus {
+        /// Start a `dbus-daemon`, or `None` if it isn't installed.
+        fn start() -> Option<Self> {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .ok()?;
+
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take()?)
+                .read_line(&mut address)
+                .ok()?;
+
+            Some(Bus {
+                daemon,
+                address: address.trim().to_string(),
+            })
+        }
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    /// Stand in for the notification daemon, recording the summary and body of every
+    /// `Notify` call until `stop` is set.
+    fn serve_notifications(
+        address: &str,
+        received: Arc<Mutex<Vec<(String, String)>>>,
+        stop: Arc<AtomicBool>,
+    ) -> thread::JoinHandle<()> {
+        let mut channel = Channel::open_private(address).unwrap();
+        channel.register().unwrap();
+        let conn = Connection::from(channel);
+        conn.request_name(NOTIFICATIONS_NAME, false, true, true)
+            .unwrap();
+
+        conn.start_receive(
+            MatchRule::new_method_call(),
+            Box::new(move |msg, conn| {
+                if msg.member().as_deref() == Some("Notify") {
+                    let (_, _, _, summary, body): (&str, u32, &str, &str, &str) =
+                        msg.read5().unwrap();
+                    received
+                        .lock()
+                        .unwrap()
+                        .push((summary.to_string(), body.to_string()));
+                    let _ = conn.send(msg.method_return().append1(1u32));
+                }
+                true
+            }),
+        );
+
+        thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                conn.process(Duration::from_millis(50)).unwrap();
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn notifications_reach_the_bus() {
+        let Some(bus) = Bus::start() else {
+            eprintln!("dbus-daemon isn't installed, skipping");
+            return;
+        };
+
+        let received = Arc::new(Mutex::new(vec![]));
+        let stop = Arc::new(AtomicBool::new(false));
+        let server = serve_notifications(&bus.address, received.clone(), stop.clone());
+
+        let notifier = Notifier::connect(&NotifyConfig {
+            events: [
+                NotifyEvent::Connected,
+                NotifyEvent::Disconnected,
+                NotifyEvent::Failed,
+            ]
+            .into_iter()
+            .collect(),
+            bus: Some(bus.address.clone()),
+        })
+        .unwrap();
+
+        notifier.notify(
+            NotifyEvent::Connected,
+            "Connected to Buds".into(),
+            Urgency::Normal,
+        );
+        notifier.notify(
+            NotifyEvent::Disconnected,
+            "Buds disconnected".into(),
+            Urgency::Normal,
+        );
+        notifier.notify(
+            NotifyEvent::Failed,
+            "Failed to pair".into(),
+            Urgency::Normal,
+        );
+        notifier.notify(
+            NotifyEvent::Paired,
+            "Paired with Buds".into(),
+            Urgency::Normal,
+        );
+        notifier.notify(
+            NotifyEvent::LowBattery,
+            "Buds battery low".into(),
+            Urgency::Critical,
+        );
+
+        // Sent in the background, so wait for them to arrive and a little longer for any
+        // that shouldn't
+        for _ in 0..100 {
+            if received.lock().unwrap().len() >= 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        stop.store(true, Ordering::Relaxed);
+        server.join().unwrap();
+
+        let mut received = received.lock().unwrap().clone();
+        received.sort();
+
+        assert_eq!(
+            received,
+            [
+                ("Bluetooth action failed", "Failed to pair"),
+                ("Device connected", "Connected to Buds"),
+                ("Device disconnected", "Buds disconnected"),
+            ]
+            .map(|(s, b)| (s.to_string(), b.to_string()))
+        );
+    }
+}
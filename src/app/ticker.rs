@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use tokio::{
+    sync::mpsc::Sender,
+    task::JoinHandle,
+    time::{interval, Duration},
+};
+
+use super::bluetool::AppEvent;
+
+/// Send a tick four times a second, so time based displays such as ages and the find mode
+/// bell keep updating while no other events arrive.
+pub fn launch_ticker(event_send_chan: Arc<Sender<AppEvent>>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticks = interval(Duration::from_millis(250));
+
+        loop {
+            ticks.tick().await;
+
+            if event_send_chan.send(AppEvent::Tick).await.is_err() {
+                break;
+            }
+        }
+    })
+}
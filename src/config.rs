@@ -0,0 +1,578 @@
+//! User configuration, read from `$XDG_CONFIG_HOME/bluetool/config.toml` and overridden by
+//! command line flags.
+
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap, HashSet},
+    env, fs,
+    path::PathBuf,
+};
+
+use anyhow::{anyhow, Context, Result};
+use bluer::Address;
+use log::LevelFilter;
+use serde::Deserialize;
+
+use crate::{
+    app::{Action, BTDevice, Bindings, Keymap, NotifyEvent},
+    display::{ColorDepth, Column, DetailsPane, IconSet, Theme},
+    xdg::config_dir,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Transport {
+    #[default]
+    Auto,
+    Le,
+    Bredr,
+}
+
+/// Order of the device table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortOrder {
+    /// Connected devices, then paired, then the rest.
+    #[default]
+    Connected,
+    Name,
+    /// Strongest signal first.
+    Signal,
+    Address,
+    /// Lowest battery first.
+    Battery,
+}
+
+impl SortOrder {
+    pub const ALL: [SortOrder; 5] = [
+        SortOrder::Connected,
+        SortOrder::Name,
+        SortOrder::Signal,
+        SortOrder::Address,
+        SortOrder::Battery,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortOrder::Connected => "connected",
+            SortOrder::Name => "name",
+            SortOrder::Signal => "signal",
+            SortOrder::Address => "address",
+            SortOrder::Battery => "battery",
+        }
+    }
+
+    pub fn compare(&self, a: &BTDevice, b: &BTDevice) -> Ordering {
+        match self {
+            SortOrder::Connected => b.cmp(a),
+            SortOrder::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            SortOrder::Signal => b.rssi.unwrap_or(i16::MIN).cmp(&a.rssi.unwrap_or(i16::MIN)),
+            SortOrder::Address => a.address.cmp(&b.address),
+            SortOrder::Battery => a
+                .battery_level()
+                .unwrap_or(u8::MAX)
+                .cmp(&b.battery_level().unwrap_or(u8::MAX)),
+        }
+    }
+}
+
+/// A Bluetooth address given in the config, e.g. `"AA:BB:CC:DD:EE:FF"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub struct DeviceAddress(pub Address);
+
+impl TryFrom<String> for DeviceAddress {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse::<Address>()
+            .map(DeviceAddress)
+            .map_err(|_| format!("invalid Bluetooth address `{}`", s))
+    }
+}
+
+/// A battery percentage from 0 to 100.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "u8")]
+pub struct Percent(pub u8);
+
+impl TryFrom<u8> for Percent {
+    type Error = String;
+
+    fn try_from(p: u8) -> Result<Self, Self::Error> {
+        match p {
+            0..=100 => Ok(Percent(p)),
+            _ => Err(format!("battery level {} is over 100%", p)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub struct LogLevel(pub LevelFilter);
+
+impl TryFrom<String> for LogLevel {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse::<LevelFilter>()
+            .map(LogLevel)
+            .map_err(|_| format!("invalid log level `{}`", s))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// Show devices which don't advertise a name.
+    pub show_unnamed: bool,
+    pub sort: SortOrder,
+    /// Columns of the device table, in order.
+    pub columns: Vec<Column>,
+    /// Where to show details of the highlighted device, if anywhere.
+    pub details: DetailsPane,
+    pub theme: Theme,
+    pub color: ColorDepth,
+    pub icons: IconSet,
+    /// Capture the mouse, for clicking and scrolling. Off leaves it to the terminal, so text
+    /// can be selected.
+    pub mouse: bool,
+    /// Adapter to use, e.g. `hci1`, instead of the default one.
+    pub adapter: Option<String>,
+    pub transport: Transport,
+    /// Only list these devices.
+    pub only: Vec<DeviceAddress>,
+    pub banner: BannerConfig,
+    pub battery: BatteryConfig,
+    pub notify: NotifyConfig,
+    pub log: LogConfig,
+    /// Key bindings replacing the defaults for each action.
+    pub keys: BTreeMap<Action, Bindings>,
+    #[serde(skip)]
+    pub keymap: Keymap,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            show_unnamed: false,
+            sort: SortOrder::default(),
+            columns: Column::DEFAULT.to_vec(),
+            details: DetailsPane::default(),
+            theme: Theme::default(),
+            color: ColorDepth::default(),
+            icons: IconSet::default(),
+            mouse: true,
+            adapter: None,
+            transport: Transport::default(),
+            only: vec![],
+            banner: BannerConfig::default(),
+            battery: BatteryConfig::default(),
+            notify: NotifyConfig::default(),
+            log: LogConfig::default(),
+            keys: BTreeMap::new(),
+            keymap: Keymap::default(),
+        }
+    }
+}
+
+/// How long banners stay up, in seconds.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct BannerConfig {
+    pub success: f64,
+    pub failure: f64,
+    pub status: f64,
+}
+
+impl Default for BannerConfig {
+    fn default() -> Self {
+        BannerConfig {
+            success: 3.0,
+            failure: 4.0,
+            status: 3.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct BatteryConfig {
+    /// Levels to alert at for every device.
+    pub low: Vec<Percent>,
+    /// Levels to alert at for particular devices, replacing `low`.
+    pub devices: HashMap<DeviceAddress, Vec<Percent>>,
+}
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        BatteryConfig {
+            low: vec![Percent(20), Percent(10)],
+            devices: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct NotifyConfig {
+    pub events: HashSet<NotifyEvent>,
+    /// D-Bus address to send notifications to, instead of the session bus.
+    pub bus: Option<String>,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        NotifyConfig {
+            events: NotifyEvent::ALL.into_iter().collect(),
+            bus: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct LogConfig {
+    pub level: Option<LogLevel>,
+    pub file: Option<PathBuf>,
+    /// Where to write events for scripts, as lines of JSON.
+    pub events: Option<PathBuf>,
+}
+
+impl Config {
+    /// Read the config file, or `--config=<path>`, then apply command line overrides.
+    pub fn load() -> Result<Self> {
+        let args = split_args(env::args().skip(1))?;
+
+        if args
+            .iter()
+            .any(|(flag, _)| flag == "--help" || flag == "-h")
+        {
+            print!("{}", USAGE);
+            std::process::exit(0);
+        }
+
+        let path = args
+            .iter()
+            .find(|(flag, _)| flag == "--config")
+            .map(|(_, value)| PathBuf::from(value));
+        let explicit = path.is_some();
+
+        let mut config = match path.or_else(|| config_dir().map(|d| d.join("config.toml"))) {
+            Some(path) => match fs::read_to_string(&path) {
+                Ok(contents) => toml::from_str(&contents)
+                    .map_err(|e| anyhow!("Invalid config {}\n{}", path.display(), e))?,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound && !explicit => {
+                    Config::default()
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to read {}", path.display()))
+                }
+            },
+            None => Config::default(),
+        };
+
+        config.apply_args(args)?;
+        config.keymap = Keymap::new(&config.keys).context("Invalid key bindings")?;
+
+        Ok(config)
+    }
+
+    fn apply_args(&mut self, args: Vec<(String, String)>) -> Result<()> {
+        for (flag, value) in args {
+            let value = value.as_str();
+
+            match flag.as_str() {
+                "--config" => {}
+                "--show-unnamed" => self.show_unnamed = parse_switch(value)?,
+                "--sort" => self.sort = parse_value(value)?,
+                "--columns" => self.columns = parse_list(value)?,
+                "--details" => self.details = parse_value(value)?,
+                "--theme" => self.theme = parse_value(value)?,
+                "--color" => self.color = parse_value(value)?,
+                "--icons" => self.icons = parse_value(value)?,
+                "--no-mouse" => self.mouse = !parse_switch(value)?,
+                "--adapter" => self.adapter = Some(value.to_string()),
+                "--le" if parse_switch(value)? => self.transport = Transport::Le,
+                "--bredr" if parse_switch(value)? => self.transport = Transport::Bredr,
+                "--le" | "--bredr" => {}
+                "--low-battery" => match value.split_once('@') {
+                    Some((levels, address)) => {
+                        self.battery
+                            .devices
+                            .insert(parse_value(address)?, parse_list_or_none(levels)?);
+                    }
+                    None => self.battery.low = parse_list_or_none(value)?,
+                },
+                "--notify" => {
+                    self.notify.events = parse_list_or_none::<NotifyEvent>(value)?
+                        .into_iter()
+                        .collect()
+                }
+                "--notify-bus" => self.notify.bus = Some(value.to_string()),
+                "--log-level" => self.log.level = Some(parse_value(value)?),
+                "--log-file" => self.log.file = Some(PathBuf::from(value)),
+                "--events" => self.log.events = Some(PathBuf::from(value)),
+                _ => match flag.parse::<Address>() {
+                    Ok(address) => self.only.push(DeviceAddress(address)),
+                    Err(_) => return Err(anyhow!("Unknown argument: {}", flag)),
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
+
+const USAGE: &str = "\
+Usage: bluetool [options] [address...]
+
+Lists Bluetooth devices, or only the addresses given. Options override the config file.
+
+Options:
+  --config=<path>         Read the config from <path>
+  --show-unnamed          Show devices which don't advertise a name
+  --sort=<order>          connected, name, signal, address or battery
+  --columns=<list>        Comma separated columns of the device table
+  --details=<pane>        hidden, right or bottom
+  --theme=<theme>         dark, light or high-contrast
+  --color=<depth>         auto, truecolor, ansi256, ansi16 or none
+  --icons=<set>           auto, nerd-font, unicode or ascii
+  --no-mouse              Leave the mouse to the terminal
+  --adapter=<name>        Use this adapter, e.g. hci1
+  --le, --bredr           Only discover LE or BR/EDR devices
+  --low-battery=<levels>  Battery levels to alert at, [levels@address] for one device, or none
+  --notify=<events>       connect, disconnect, pair, fail and battery, or none
+  --notify-bus=<address>  Send notifications to this D-Bus address
+  --log-level=<level>     error, warn, info, debug or trace
+  --log-file=<path>       Log to <path>
+  --events=<path>         Write low battery alerts to <path> as lines of JSON
+  -h, --help              Show this help
+";
+
+/// Flags which take a value, with what to call it when it's missing.
+const VALUE_FLAGS: [(&str, &str); 14] = [
+    ("--config", "path"),
+    ("--sort", "order"),
+    ("--columns", "list"),
+    ("--details", "pane"),
+    ("--theme", "theme"),
+    ("--color", "depth"),
+    ("--icons", "set"),
+    ("--adapter", "name"),
+    ("--low-battery", "levels"),
+    ("--notify", "events"),
+    ("--notify-bus", "address"),
+    ("--log-level", "level"),
+    ("--log-file", "path"),
+    ("--events", "path"),
+];
+
+/// Pair each flag with its value, given either as `--flag=value` or `--flag value`. Other
+/// arguments come with an empty value.
+fn split_args(args: impl Iterator<Item = String>) -> Result<Vec<(String, String)>> {
+    let mut args = args.peekable();
+    let mut flags = vec![];
+
+    while let Some(arg) = args.next() {
+        let (flag, value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+
+        let value = match VALUE_FLAGS.iter().find(|(f, _)| *f == flag) {
+            Some((_, name)) => match value.or_else(|| args.next_if(|a| !a.starts_with("--"))) {
+                Some(value) if !value.is_empty() => value,
+                _ => return Err(anyhow!("`{}` needs a value (`{}=<{}>`)", flag, flag, name)),
+            },
+            None => value.unwrap_or_default(),
+        };
+
+        flags.push((flag, value));
+    }
+
+    Ok(flags)
+}
+
+/// Parse a flag's value the same way as it would be read from the config file.
+fn parse_value<T: for<'de> Deserialize<'de>>(value: &str) -> Result<T> {
+    let number = value.trim_end_matches('%');
+    let toml_value = match (number.parse::<i64>(), value.parse::<bool>()) {
+        (Ok(n), _) => toml::Value::Integer(n),
+        (_, Ok(b)) => toml::Value::Boolean(b),
+        _ => toml::Value::String(value.to_string()),
+    };
+
+    T::deserialize(toml_value).map_err(|e| anyhow!("Invalid value `{}`: {}", value, e))
+}
+
+/// Parse a flag which is on by itself, or set with `--flag=true` or `--flag=false`.
+fn parse_switch(value: &str) -> Result<bool> {
+    if value.is_empty() {
+        Ok(true)
+    } else {
+        parse_value(value)
+    }
+}
+
+/// Parse a comma separated flag value into a list.
+fn parse_list<T: for<'de> Deserialize<'de>>(value: &str) -> Result<Vec<T>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(parse_value)
+        .collect()
+}
+
+/// A comma separated list, or `none` on its own for an empty one.
+fn parse_list_or_none<T: for<'de> Deserialize<'de>>(value: &str) -> Result<Vec<T>> {
+    if value.trim() == "none" {
+        return Ok(vec![]);
+    }
+
+    if value.split(',').any(|v| v.trim() == "none") {
+        return Err(anyhow!(
+            "`none` can't be combined with other values: {}",
+            value
+        ));
+    }
+
+    parse_list(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Vec<(String, String)>> {
+        split_args(args.iter().map(|a| a.to_string()))
+    }
+
+    fn config(a: &[&str]) -> Result<Config> {
+        let mut config = Config::default();
+        config.apply_args(args(a)?)?;
+        Ok(config)
+    }
+
+    #[test]
+    fn split() {
+        let pairs = |p: &[(&str, &str)]| {
+            p.iter()
+                .map(|(f, v)| (f.to_string(), v.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            args(&["--sort=name", "--theme", "light", "--le"]).unwrap(),
+            pairs(&[("--sort", "name"), ("--theme", "light"), ("--le", "")])
+        );
+        assert_eq!(
+            args(&["--show-unnamed=false", "AA:BB:CC:DD:EE:FF"]).unwrap(),
+            pairs(&[("--show-unnamed", "false"), ("AA:BB:CC:DD:EE:FF", "")])
+        );
+
+        // Flags without a value don't take the next argument
+        assert_eq!(
+            args(&["--le", "AA:BB:CC:DD:EE:FF"]).unwrap(),
+            pairs(&[("--le", ""), ("AA:BB:CC:DD:EE:FF", "")])
+        );
+
+        assert!(args(&["--sort"]).is_err());
+        assert!(args(&["--sort="]).is_err());
+        assert!(args(&["--sort", "--le"]).is_err());
+    }
+
+    #[test]
+    fn values() {
+        assert_eq!(
+            parse_value::<SortOrder>("signal").unwrap(),
+            SortOrder::Signal
+        );
+        assert_eq!(parse_value::<Percent>("30%").unwrap(), Percent(30));
+        assert_eq!(parse_value::<Percent>("30").unwrap(), Percent(30));
+        assert!(parse_value::<Percent>("130").is_err());
+        assert!(parse_value::<bool>("false").is_ok_and(|b| !b));
+        assert!(parse_value::<SortOrder>("loudest").is_err());
+
+        assert_eq!(
+            parse_list::<Percent>("30, 15,").unwrap(),
+            [Percent(30), Percent(15)]
+        );
+        assert!(parse_list::<Percent>("").unwrap().is_empty());
+
+        assert!(parse_list_or_none::<Percent>("none").unwrap().is_empty());
+        assert_eq!(
+            parse_list_or_none::<Percent>("30,15").unwrap(),
+            [Percent(30), Percent(15)]
+        );
+        assert!(parse_list_or_none::<Percent>("none,30").is_err());
+        assert!(parse_list_or_none::<NotifyEvent>("none,connect").is_err());
+        assert!(parse_list_or_none::<NotifyEvent>("nonesuch").is_err());
+    }
+
+    #[test]
+    fn switches() {
+        assert!(config(&["--show-unnamed"]).unwrap().show_unnamed);
+        assert!(config(&["--show-unnamed=true"]).unwrap().show_unnamed);
+        assert!(!config(&["--show-unnamed=false"]).unwrap().show_unnamed);
+        assert!(config(&["--show-unnamed=maybe"]).is_err());
+
+        assert!(!config(&["--no-mouse"]).unwrap().mouse);
+        assert!(config(&["--no-mouse=false"]).unwrap().mouse);
+
+        assert_eq!(config(&["--le"]).unwrap().transport, Transport::Le);
+        assert_eq!(config(&["--le=false"]).unwrap().transport, Transport::Auto);
+        assert_eq!(config(&["--bredr"]).unwrap().transport, Transport::Bredr);
+    }
+
+    #[test]
+    fn apply() {
+        let config = config(&[
+            "--sort",
+            "battery",
+            "--columns=name,battery",
+            "--low-battery=30,15",
+            "--low-battery=50@AA:BB:CC:DD:EE:FF",
+            "--notify=none",
+            "--adapter=hci1",
+            "--events",
+            "/dev/fd/3",
+            "11:22:33:44:55:66",
+        ])
+        .unwrap();
+
+        assert_eq!(config.sort, SortOrder::Battery);
+        assert_eq!(config.columns, [Column::Name, Column::Battery]);
+        assert_eq!(config.battery.low, [Percent(30), Percent(15)]);
+        assert_eq!(
+            config.battery.devices[&"AA:BB:CC:DD:EE:FF".to_string().try_into().unwrap()],
+            [Percent(50)]
+        );
+        assert!(config.notify.events.is_empty());
+        assert_eq!(config.adapter.as_deref(), Some("hci1"));
+        assert_eq!(config.log.events, Some(PathBuf::from("/dev/fd/3")));
+        assert_eq!(
+            config.only,
+            [DeviceAddress("11:22:33:44:55:66".parse().unwrap())]
+        );
+
+        assert!(Config::default()
+            .apply_args(args(&["--bogus"]).unwrap())
+            .is_err());
+        assert!(Config::default()
+            .apply_args(args(&["--sort=loudest"]).unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn none() {
+        let none = config(&["--low-battery=none@AA:BB:CC:DD:EE:FF", "--notify=none"]).unwrap();
+        assert_eq!(none.battery.devices.len(), 1);
+        assert!(none.battery.devices.values().all(Vec::is_empty));
+        assert!(none.notify.events.is_empty());
+
+        assert!(config(&["--low-battery=none,10"]).is_err());
+        assert!(config(&["--notify=none,connect"]).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+//! Classify device addresses, and look up the vendor of public addresses.
+//!
+//! Public addresses start with an IEEE assigned OUI. LE random addresses encode their
+//! kind in the two most significant bits, which tells us whether the address survives
+//! a power cycle (static) or rotates every few minutes (private).
+
+use std::fmt;
+
+use bluer::{Address, AddressType};
+
+/// Offline subset of the IEEE OUI registry, for vendors commonly seen over Bluetooth.
+static OUI_VENDORS: &[(u32, &str)] = &[
+    (0x000393, "Apple"),
+    (0x000502, "Apple"),
+    (0x000761, "Logitech"),
+    (0x0009BF, "Nintendo"),
+    (0x000A27, "Apple"),
+    (0x000A95, "Apple"),
+    (0x000D93, "Apple"),
+    (0x0010FA, "Apple"),
+    (0x001124, "Apple"),
+    (0x00124B, "Texas Instruments"),
+    (0x0012FB, "Samsung"),
+    (0x0013A9, "Sony"),
+    (0x001451, "Apple"),
+    (0x001656, "Nintendo"),
+    (0x0016CB, "Apple"),
+    (0x0017AB, "Nintendo"),
+    (0x0017F2, "Apple"),
+    (0x00191D, "Nintendo"),
+    (0x00197F, "Plantronics"),
+    (0x0019C1, "Alps Electric"),
+    (0x0019E3, "Apple"),
+    (0x001A11, "Google"),
+    (0x001A7D, "cyber-blue(HK)"),
+    (0x001AE9, "Nintendo"),
+    (0x001B63, "Apple"),
+    (0x001B66, "Sennheiser"),
+    (0x001B7A, "Nintendo"),
+    (0x001BEA, "Nintendo"),
+    (0x001CB3, "Apple"),
+    (0x001CBE, "Nintendo"),
+    (0x001D4F, "Apple"),
+    (0x001DBA, "Sony"),
+    (0x001DBC, "Nintendo"),
+    (0x001E35, "Nintendo"),
+    (0x001E52, "Apple"),
+    (0x001EC2, "Apple"),
+    (0x001F20, "Logitech"),
+    (0x001F32, "Nintendo"),
+    (0x001F5B, "Apple"),
+    (0x001FC5, "Nintendo"),
+    (0x001FF3, "Apple"),
+    (0x002147, "Nintendo"),
+    (0x0021BD, "Nintendo"),
+    (0x0021E9, "Apple"),
+    (0x002241, "Apple"),
+    (0x00224C, "Nintendo"),
+    (0x0022AA, "Nintendo"),
+    (0x0022D7, "Nintendo"),
+    (0x002312, "Apple"),
+    (0x002331, "Nintendo"),
+    (0x002332, "Apple"),
+    (0x00236C, "Apple"),
+    (0x0023CC, "Nintendo"),
+    (0x0023DF, "Apple"),
+    (0x00241E, "Nintendo"),
+    (0x002436, "Apple"),
+    (0x002444, "Nintendo"),
+    (0x0024BE, "Sony"),
+    (0x0024F3, "Nintendo"),
+    (0x002500, "Apple"),
+    (0x00254B, "Apple"),
+    (0x0025A0, "Nintendo"),
+    (0x0025BC, "Apple"),
+    (0x002608, "Apple"),
+    (0x00264A, "Apple"),
+    (0x002659, "Nintendo"),
+    (0x0026B0, "Apple"),
+    (0x0026BB, "Apple"),
+    (0x002709, "Nintendo"),
+    (0x0452C7, "Bose"),
+    (0x0C47C9, "Amazon"),
+    (0x10521C, "Espressif"),
+    (0x240AC4, "Espressif"),
+    (0x2462AB, "Espressif"),
+    (0x246F28, "Espressif"),
+    (0x286C07, "Xiaomi"),
+    (0x28CDC1, "Raspberry Pi"),
+    (0x2C41A1, "Bose"),
+    (0x2CCF67, "Raspberry Pi"),
+    (0x30AEA4, "Espressif"),
+    (0x30C6F7, "Espressif"),
+    (0x3480B3, "Xiaomi"),
+    (0x3C0754, "Apple"),
+    (0x3C5AB4, "Google"),
+    (0x3C71BF, "Espressif"),
+    (0x406C8F, "Apple"),
+    (0x44650D, "Amazon"),
+    (0x483FDA, "Espressif"),
+    (0x48C1AC, "Plantronics"),
+    (0x4C875D, "Bose"),
+    (0x50C2ED, "GN Audio"),
+    (0x546009, "Google"),
+    (0x58BF25, "Espressif"),
+    (0x600194, "Espressif"),
+    (0x6854FD, "Amazon"),
+    (0x68C63A, "Espressif"),
+    (0x74C246, "Amazon"),
+    (0x78CA39, "Apple"),
+    (0x7C1E52, "Microsoft"),
+    (0x7C9EBD, "Espressif"),
+    (0x807D3A, "Espressif"),
+    (0x84CCA8, "Espressif"),
+    (0x84F3EB, "Espressif"),
+    (0x8CAAB5, "Espressif"),
+    (0x94B97E, "Espressif"),
+    (0x98B6E9, "Nintendo"),
+    (0x98F4AB, "Espressif"),
+    (0xA020A6, "Espressif"),
+    (0xA4C138, "Telink"),
+    (0xA4CF12, "Espressif"),
+    (0xAC67B2, "Espressif"),
+    (0xACBC32, "Apple"),
+    (0xB0B448, "Texas Instruments"),
+    (0xB4E62D, "Espressif"),
+    (0xB827EB, "Raspberry Pi"),
+    (0xBCDDC2, "Espressif"),
+    (0xC44F33, "Espressif"),
+    (0xCC50E3, "Espressif"),
+    (0xD83ADD, "Raspberry Pi"),
+    (0xDC4F22, "Espressif"),
+    (0xDCA632, "Raspberry Pi"),
+    (0xE45F01, "Raspberry Pi"),
+    (0xE8DB84, "Espressif"),
+    (0xECFABC, "Espressif"),
+    (0xF0272D, "Amazon"),
+    (0xF4F5D8, "Google"),
+    (0xF88FCA, "Google"),
+];
+
+/// How a device address was assigned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressKind {
+    /// An IEEE assigned public address, with the OUI vendor if known.
+    Public(Option<&'static str>),
+    /// A random address that stays the same until the device is power cycled.
+    RandomStatic,
+    /// A random address that rotates, and can only be resolved with the device's IRK.
+    ResolvablePrivate,
+    /// A random address that rotates, and can't be resolved back to the device.
+    NonResolvable,
+    /// A random address using the reserved `0b10` prefix.
+    Reserved,
+}
+
+impl AddressKind {
+    pub fn classify(address: Address, address_type: Option<AddressType>) -> Self {
+        match address_type {
+            Some(AddressType::LeRandom) => match address.0[0] >> 6 {
+                0b11 => AddressKind::RandomStatic,
+                0b01 => AddressKind::ResolvablePrivate,
+                0b00 => AddressKind::NonResolvable,
+                _ => AddressKind::Reserved,
+            },
+            _ => AddressKind::Public(oui_vendor(address)),
+        }
+    }
+
+    /// Whether the address can be expected to identify the same device over time.
+    pub fn is_stable(&self) -> bool {
+        matches!(self, AddressKind::Public(_) | AddressKind::RandomStatic)
+    }
+}
+
+impl fmt::Display for AddressKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AddressKind::Public(Some(vendor)) => write!(f, "{}", vendor),
+            AddressKind::Public(None) => write!(f, "Public"),
+            AddressKind::RandomStatic => write!(f, "Random static"),
+            AddressKind::ResolvablePrivate => write!(f, "Resolvable private"),
+            AddressKind::NonResolvable => write!(f, "Non-resolvable"),
+            AddressKind::Reserved => write!(f, "Reserved"),
+        }
+    }
+}
+
+/// Look up the vendor owning the OUI of a public address.
+pub fn oui_vendor(address: Address) -> Option<&'static str> {
+    let oui = u32::from_be_bytes([0, address.0[0], address.0[1], address.0[2]]);
+
+    OUI_VENDORS
+        .binary_search_by_key(&oui, |&(k, _)| k)
+        .ok()
+        .map(|idx| OUI_VENDORS[idx].1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(address: &str, address_type: Option<AddressType>) -> AddressKind {
+        AddressKind::classify(address.parse().unwrap(), address_type)
+    }
+
+    #[test]
+    fn public() {
+        assert_eq!(
+            classify("B8:27:EB:12:34:56", Some(AddressType::LePublic)),
+            AddressKind::Public(Some("Raspberry Pi"))
+        );
+        assert_eq!(
+            classify("00:03:93:12:34:56", Some(AddressType::BrEdr)),
+            AddressKind::Public(Some("Apple"))
+        );
+        assert_eq!(
+            classify("12:34:56:78:9A:BC", Some(AddressType::LePublic)),
+            AddressKind::Public(None)
+        );
+
+        // The top bits of a public address mean nothing
+        assert_eq!(
+            classify("F8:8F:CA:00:00:01", None),
+            AddressKind::Public(Some("Google"))
+        );
+        assert!(AddressKind::Public(None).is_stable());
+        assert_eq!(classify("12:34:56:78:9A:BC", None).to_string(), "Public");
+    }
+
+    #[test]
+    fn random() {
+        let random = |a| classify(a, Some(AddressType::LeRandom));
+
+        // 0b11......
+        assert_eq!(random("C0:00:00:00:00:00"), AddressKind::RandomStatic);
+        assert_eq!(random("FF:FF:FF:FF:FF:FF"), AddressKind::RandomStatic);
+        // 0b01......
+        assert_eq!(random("40:11:22:33:44:55"), AddressKind::ResolvablePrivate);
+        assert_eq!(random("7F:11:22:33:44:55"), AddressKind::ResolvablePrivate);
+        // 0b00......
+        assert_eq!(random("3F:11:22:33:44:55"), AddressKind::NonResolvable);
+        assert_eq!(random("00:11:22:33:44:55"), AddressKind::NonResolvable);
+        // 0b10......
+        assert_eq!(random("80:11:22:33:44:55"), AddressKind::Reserved);
+
+        // A random address with a known OUI's bits isn't that vendor's
+        assert_eq!(random("F8:8F:CA:00:00:01"), AddressKind::RandomStatic);
+
+        assert!(AddressKind::RandomStatic.is_stable());
+        assert!(!AddressKind::ResolvablePrivate.is_stable());
+        assert!(!AddressKind::NonResolvable.is_stable());
+    }
+
+    #[test]
+    fn vendors_are_sorted() {
+        assert!(OUI_VENDORS.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(
+            oui_vendor("00:0A:95:00:00:00".parse().unwrap()),
+            Some("Apple")
+        );
+        assert_eq!(oui_vendor("00:0A:96:00:00:00".parse().unwrap()), None);
+    }
+}
@@ -0,0 +1,100 @@
+//! Decode the 16 bit GAP Appearance advertised by LE devices.
+//!
+//! The top 10 bits hold the category and the bottom 6 bits the subcategory.
+
+use std::fmt;
+
+use super::assigned_numbers::format_appearance;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Appearance(pub u16);
+
+impl Appearance {
+    pub fn category(&self) -> u16 {
+        self.0 >> 6
+    }
+
+    pub fn subcategory(&self) -> u16 {
+        self.0 & 0x3F
+    }
+
+    /// A freedesktop style icon name for this appearance, matching the names BlueZ
+    /// reports for classic devices.
+    pub fn icon_name(&self) -> Option<&'static str> {
+        match (self.category(), self.subcategory()) {
+            (0x001, _) => Some("phone"),
+            (0x002, 7) => Some("tablet"),
+            (0x002, _) => Some("computer"),
+            (0x003, _) | (0x004, _) => Some("watch"),
+            (0x005, _) | (0x028, _) => Some("video-display"),
+            (0x008, _) | (0x009, _) => Some("tag"),
+            (0x00A, _) => Some("audio"),
+            (0x00C, _) => Some("thermometer"),
+            (0x00D, _) | (0x00E, _) | (0x031, _) => Some("heart-rate"),
+            (0x00F, 1) => Some("input-keyboard"),
+            (0x00F, 2) | (0x00F, 9) => Some("input-mouse"),
+            (0x00F, 3) | (0x00F, 4) => Some("input-gaming"),
+            (0x00F, 5) => Some("input-tablet"),
+            (0x015, _) => Some("sensor"),
+            (0x016, _) | (0x01F, _) => Some("smart-light"),
+            (0x021, _) => Some("audio-speakers"),
+            (0x022, 1) => Some("microphone"),
+            (0x025, 2) => Some("audio-headset"),
+            (0x025, _) => Some("audio-headphones"),
+            (0x029, _) => Some("hearing-aid"),
+            (0x02A, _) => Some("input-gaming"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Appearance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format_appearance(self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyboard() {
+        let appearance = Appearance(0x03C1);
+
+        assert_eq!(appearance.category(), 0x00F);
+        assert_eq!(appearance.subcategory(), 1);
+        assert_eq!(
+            appearance.to_string(),
+            "Human Interface Device: Keyboard (0x03C1)"
+        );
+        assert_eq!(appearance.icon_name(), Some("input-keyboard"));
+    }
+
+    #[test]
+    fn earbuds() {
+        let earbuds = Appearance(0x0941);
+
+        assert_eq!(
+            earbuds.to_string(),
+            "Wearable Audio Device: Earbud (0x0941)"
+        );
+        assert_eq!(earbuds.icon_name(), Some("audio-headphones"));
+        assert_eq!(Appearance(0x0942).icon_name(), Some("audio-headset"));
+    }
+
+    #[test]
+    fn generic_subcategory() {
+        let generic = Appearance(0x0940);
+
+        assert_eq!(generic.to_string(), "Wearable Audio Device (0x0940)");
+        assert_eq!(generic.icon_name(), Some("audio-headphones"));
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(Appearance(0x0000).icon_name(), None);
+        assert_eq!(Appearance(0xFFC0).to_string(), "Unknown (0xFFC0)");
+        assert_eq!(Appearance(0xFFC0).icon_name(), None);
+    }
+}
@@ -0,0 +1,288 @@
+//! Decode Apple Continuity proximity pairing messages.
+//!
+//! AirPods and Beats headphones don't expose a GATT battery service, but advertise their
+//! battery, charging and in-ear state in proximity pairing messages (type `0x07`) inside
+//! Apple's manufacturer data.
+
+use super::decoded::Decoded;
+
+pub const APPLE_COMPANY_ID: u16 = 0x004C;
+const PROXIMITY_PAIRING: u8 = 0x07;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ComponentStatus {
+    /// Battery percentage, in steps of 10. `None` when the component isn't reporting.
+    pub battery: Option<u8>,
+    pub charging: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProximityPairing {
+    pub model: u16,
+    pub left: ComponentStatus,
+    pub right: ComponentStatus,
+    pub case: ComponentStatus,
+    pub left_in_ear: bool,
+    pub right_in_ear: bool,
+}
+
+impl ProximityPairing {
+    /// Find and parse a proximity pairing message in Apple's manufacturer data.
+    pub fn from_manufacturer_data(data: &[u8]) -> Option<Self> {
+        let mut rest = data;
+
+        while let [msg_type, len, tail @ ..] = rest {
+            let payload = tail.get(..*len as usize)?;
+
+            if *msg_type == PROXIMITY_PAIRING {
+                return Self::from_payload(payload);
+            }
+
+            rest = &tail[*len as usize..];
+        }
+
+        None
+    }
+
+    pub fn from_payload(payload: &[u8]) -> Option<Self> {
+        let [_prefix, model_hi, model_lo, status, battery, charge_case, ..] = payload else {
+            return None;
+        };
+
+        let model = u16::from_be_bytes([*model_hi, *model_lo]);
+
+        // The pod which is broadcasting reports itself in the low nibble, so left and right
+        // swap depending on which pod is the primary.
+        let flipped = status & 0x20 == 0;
+
+        let (left_nibble, right_nibble) = if flipped {
+            (battery >> 4, battery & 0x0F)
+        } else {
+            (battery & 0x0F, battery >> 4)
+        };
+
+        let charging = charge_case >> 4;
+        let (left_charging, right_charging) = if flipped {
+            (charging & 0x02 != 0, charging & 0x01 != 0)
+        } else {
+            (charging & 0x01 != 0, charging & 0x02 != 0)
+        };
+
+        let (left_in_ear, right_in_ear) = if flipped {
+            (status & 0x08 != 0, status & 0x02 != 0)
+        } else {
+            (status & 0x02 != 0, status & 0x08 != 0)
+        };
+
+        let mut pairing = ProximityPairing {
+            model,
+            left: ComponentStatus {
+                battery: battery_level(left_nibble),
+                charging: left_charging,
+            },
+            right: ComponentStatus {
+                battery: battery_level(right_nibble),
+                charging: right_charging,
+            },
+            case: ComponentStatus {
+                battery: battery_level(charge_case & 0x0F),
+                charging: charging & 0x04 != 0,
+            },
+            left_in_ear,
+            right_in_ear,
+        };
+
+        // Over-ear headphones report a single battery in the low nibble
+        if pairing.is_single_battery() {
+            pairing.left = ComponentStatus {
+                battery: battery_level(battery & 0x0F),
+                charging: charging & 0x01 != 0,
+            };
+            pairing.right = ComponentStatus::default();
+            pairing.case = ComponentStatus::default();
+        }
+
+        Some(pairing)
+    }
+
+    pub fn model_name(&self) -> &'static str {
+        match self.model {
+            0x0220 => "AirPods",
+            0x0F20 => "AirPods (2nd generation)",
+            0x1320 => "AirPods (3rd generation)",
+            0x0E20 => "AirPods Pro",
+            0x1420 => "AirPods Pro (2nd generation)",
+            0x0A20 => "AirPods Max",
+            0x0320 => "Powerbeats3",
+            0x0B20 => "Powerbeats Pro",
+            0x0520 => "BeatsX",
+            0x0620 => "Beats Solo3",
+            0x0920 => "Beats Studio3",
+            0x0C20 => "Beats Solo Pro",
+            0x1020 => "Beats Flex",
+            0x1120 => "Beats Studio Buds",
+            0x1220 => "Beats Fit Pro",
+            0x1620 => "Beats Studio Buds+",
+            0x1720 => "Beats Studio Pro",
+            _ => "Unknown Apple accessory",
+        }
+    }
+
+    fn is_single_battery(&self) -> bool {
+        matches!(self.model, 0x0A20 | 0x0620 | 0x0920 | 0x0C20 | 0x1720)
+    }
+
+    /// Labelled battery levels of every component currently reporting one.
+    pub fn levels(&self) -> Vec<(&'static str, u8)> {
+        if self.is_single_battery() {
+            return self.left.battery.map(|b| ("", b)).into_iter().collect();
+        }
+
+        [("L", self.left), ("R", self.right), ("C", self.case)]
+            .into_iter()
+            .filter_map(|(label, c)| c.battery.map(|b| (label, b)))
+            .collect()
+    }
+
+    pub fn to_decoded(self) -> Decoded {
+        let status = |c: &ComponentStatus| match (c.battery, c.charging) {
+            (Some(b), true) => format!("{}%, charging", b),
+            (Some(b), false) => format!("{}%", b),
+            (None, _) => "Not reporting".to_string(),
+        };
+
+        let decoded = Decoded::new("Apple Continuity: Proximity Pairing").field(
+            "Model",
+            format!("{} (0x{:04X})", self.model_name(), self.model),
+        );
+
+        if self.is_single_battery() {
+            return decoded.field("Battery", status(&self.left));
+        }
+
+        let in_ear = |b: bool| if b { "in ear" } else { "out of ear" };
+
+        decoded
+            .field(
+                "Left",
+                format!("{}, {}", status(&self.left), in_ear(self.left_in_ear)),
+            )
+            .field(
+                "Right",
+                format!("{}, {}", status(&self.right), in_ear(self.right_in_ear)),
+            )
+            .field("Case", status(&self.case))
+    }
+}
+
+/// Levels are reported in tenths, with 15 meaning the component isn't connected.
+fn battery_level(nibble: u8) -> Option<u8> {
+    match nibble {
+        0..=10 => Some(nibble * 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An AirPods Pro proximity pairing message, with the encrypted tail zeroed.
+    fn airpods_pro(status: u8, battery: u8, charge_case: u8) -> Vec<u8> {
+        let mut data = vec![0x07, 0x19, 0x01, 0x0E, 0x20, status, battery, charge_case];
+        data.extend([0x01, 0x00, 0x00]);
+        data.extend([0; 16]);
+        data
+    }
+
+    #[test]
+    fn left_pod_primary() {
+        let pairing =
+            ProximityPairing::from_manufacturer_data(&airpods_pro(0x23, 0x98, 0x55)).unwrap();
+
+        assert_eq!(pairing.model_name(), "AirPods Pro");
+        assert_eq!(
+            pairing.left,
+            ComponentStatus {
+                battery: Some(80),
+                charging: true,
+            }
+        );
+        assert_eq!(
+            pairing.right,
+            ComponentStatus {
+                battery: Some(90),
+                charging: false,
+            }
+        );
+        assert_eq!(
+            pairing.case,
+            ComponentStatus {
+                battery: Some(50),
+                charging: true,
+            }
+        );
+        assert!(pairing.left_in_ear);
+        assert!(!pairing.right_in_ear);
+        assert_eq!(pairing.levels(), [("L", 80), ("R", 90), ("C", 50)]);
+    }
+
+    #[test]
+    fn right_pod_primary() {
+        // Without the 0x20 status bit the nibbles, charging bits and in-ear bits swap sides
+        let pairing =
+            ProximityPairing::from_manufacturer_data(&airpods_pro(0x03, 0x98, 0x55)).unwrap();
+
+        assert_eq!(pairing.left.battery, Some(90));
+        assert_eq!(pairing.right.battery, Some(80));
+        assert!(!pairing.left.charging);
+        assert!(pairing.right.charging);
+        assert!(!pairing.left_in_ear);
+        assert!(pairing.right_in_ear);
+    }
+
+    #[test]
+    fn case_not_reporting() {
+        let pairing =
+            ProximityPairing::from_manufacturer_data(&airpods_pro(0x23, 0x98, 0x0F)).unwrap();
+
+        assert_eq!(pairing.case.battery, None);
+        assert_eq!(pairing.levels(), [("L", 80), ("R", 90)]);
+
+        let decoded = pairing.to_decoded();
+        assert_eq!(decoded.fields[3], ("Case".into(), "Not reporting".into()));
+    }
+
+    #[test]
+    fn single_battery() {
+        let data = [0x07, 0x06, 0x01, 0x0A, 0x20, 0x20, 0x07, 0x10];
+        let pairing = ProximityPairing::from_manufacturer_data(&data).unwrap();
+
+        assert_eq!(pairing.model_name(), "AirPods Max");
+        assert_eq!(pairing.levels(), [("", 70)]);
+        assert!(pairing.left.charging);
+        assert_eq!(pairing.case, ComponentStatus::default());
+    }
+
+    #[test]
+    fn skips_other_messages() {
+        let mut data = vec![0x10, 0x02, 0x0B, 0x1C];
+        data.extend(airpods_pro(0x23, 0x98, 0x55));
+
+        assert!(ProximityPairing::from_manufacturer_data(&data).is_some());
+    }
+
+    #[test]
+    fn truncated() {
+        let data = airpods_pro(0x23, 0x98, 0x55);
+
+        assert_eq!(ProximityPairing::from_manufacturer_data(&[]), None);
+        assert_eq!(ProximityPairing::from_manufacturer_data(&data[..10]), None);
+        assert_eq!(ProximityPairing::from_manufacturer_data(&[0x07]), None);
+        assert_eq!(ProximityPairing::from_payload(&data[2..7]), None);
+        assert_eq!(
+            ProximityPairing::from_manufacturer_data(&[0x10, 0x05, 0x0B]),
+            None
+        );
+    }
+}
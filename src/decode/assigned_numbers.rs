@@ -0,0 +1,699 @@
+//! Offline subset of the Bluetooth SIG assigned numbers.
+//!
+//! Covers the 16-bit service, characteristic and descriptor UUIDs, company identifiers
+//! and appearance values that are commonly seen in the wild. Users can name their own
+//! UUIDs in `$XDG_CONFIG_HOME/bluetool/uuids`.
+
+use std::{collections::HashMap, fs, sync::OnceLock};
+
+use anyhow::{Context, Result};
+use bluer::{Uuid, UuidExt};
+
+use crate::xdg::config_dir;
+
+pub const BATTERY_SERVICE: u16 = 0x180F;
+pub const BATTERY_LEVEL: u16 = 0x2A19;
+
+static CUSTOM_UUID_NAMES: OnceLock<HashMap<Uuid, String>> = OnceLock::new();
+
+static SERVICES: &[(u16, &str)] = &[
+    // BR/EDR service classes, as reported in the UUIDs of classic devices
+    (0x1000, "Service Discovery Server"),
+    (0x1101, "Serial Port"),
+    (0x1103, "Dialup Networking"),
+    (0x1105, "OBEX Object Push"),
+    (0x1106, "OBEX File Transfer"),
+    (0x1108, "Headset"),
+    (0x110A, "Audio Source"),
+    (0x110B, "Audio Sink"),
+    (0x110C, "A/V Remote Control Target"),
+    (0x110D, "Advanced Audio Distribution"),
+    (0x110E, "A/V Remote Control"),
+    (0x110F, "A/V Remote Control Controller"),
+    (0x1112, "Headset Audio Gateway"),
+    (0x1115, "PAN User"),
+    (0x1116, "Network Access Point"),
+    (0x1117, "Group Ad-hoc Network"),
+    (0x111E, "Handsfree"),
+    (0x111F, "Handsfree Audio Gateway"),
+    (0x1124, "Human Interface Device"),
+    (0x112D, "SIM Access"),
+    (0x112E, "Phonebook Access Client"),
+    (0x112F, "Phonebook Access Server"),
+    (0x1130, "Phonebook Access"),
+    (0x1131, "Headset HS"),
+    (0x1132, "Message Access Server"),
+    (0x1133, "Message Notification Server"),
+    (0x1134, "Message Access Profile"),
+    (0x1200, "PnP Information"),
+    (0x1203, "Generic Audio"),
+    // GATT services
+    (0x1800, "Generic Access"),
+    (0x1801, "Generic Attribute"),
+    (0x1802, "Immediate Alert"),
+    (0x1803, "Link Loss"),
+    (0x1804, "Tx Power"),
+    (0x1805, "Current Time"),
+    (0x1806, "Reference Time Update"),
+    (0x1807, "Next DST Change"),
+    (0x1808, "Glucose"),
+    (0x1809, "Health Thermometer"),
+    (0x180A, "Device Information"),
+    (0x180D, "Heart Rate"),
+    (0x180E, "Phone Alert Status"),
+    (0x180F, "Battery Service"),
+    (0x1810, "Blood Pressure"),
+    (0x1811, "Alert Notification"),
+    (0x1812, "Human Interface Device"),
+    (0x1813, "Scan Parameters"),
+    (0x1814, "Running Speed and Cadence"),
+    (0x1815, "Automation IO"),
+    (0x1816, "Cycling Speed and Cadence"),
+    (0x1818, "Cycling Power"),
+    (0x1819, "Location and Navigation"),
+    (0x181A, "Environmental Sensing"),
+    (0x181B, "Body Composition"),
+    (0x181C, "User Data"),
+    (0x181D, "Weight Scale"),
+    (0x181E, "Bond Management"),
+    (0x181F, "Continuous Glucose Monitoring"),
+    (0x1820, "Internet Protocol Support"),
+    (0x1821, "Indoor Positioning"),
+    (0x1822, "Pulse Oximeter"),
+    (0x1823, "HTTP Proxy"),
+    (0x1824, "Transport Discovery"),
+    (0x1825, "Object Transfer"),
+    (0x1826, "Fitness Machine"),
+    (0x1827, "Mesh Provisioning"),
+    (0x1828, "Mesh Proxy"),
+    (0x1829, "Reconnection Configuration"),
+    (0x183A, "Insulin Delivery"),
+    (0x183B, "Binary Sensor"),
+    (0x183C, "Emergency Configuration"),
+    (0x183E, "Physical Activity Monitor"),
+    (0x1843, "Audio Input Control"),
+    (0x1844, "Volume Control"),
+    (0x1845, "Volume Offset Control"),
+    (0x1846, "Coordinated Set Identification"),
+    (0x1847, "Device Time"),
+    (0x1848, "Media Control"),
+    (0x1849, "Generic Media Control"),
+    (0x184A, "Constant Tone Extension"),
+    (0x184B, "Telephone Bearer"),
+    (0x184C, "Generic Telephone Bearer"),
+    (0x184D, "Microphone Control"),
+    (0x184E, "Audio Stream Control"),
+    (0x184F, "Broadcast Audio Scan"),
+    (0x1850, "Published Audio Capabilities"),
+    (0x1851, "Basic Audio Announcement"),
+    (0x1852, "Broadcast Audio Announcement"),
+    (0x1853, "Common Audio"),
+    (0x1854, "Hearing Access"),
+    (0x1855, "Telephony and Media Audio"),
+    (0x1856, "Public Broadcast Announcement"),
+    // Member services
+    (0xFCD2, "BTHome"),
+    (0xFD6F, "Exposure Notification"),
+    (0xFE0F, "Signify Netherlands B.V."),
+    (0xFE2C, "Google Fast Pair"),
+    (0xFE59, "Nordic Secure DFU"),
+    (0xFE95, "Xiaomi Inc."),
+    (0xFE9A, "Estimote"),
+    (0xFEAA, "Eddystone"),
+    (0xFEE0, "Anhui Huami Information Technology"),
+    (0xFEE7, "Tencent"),
+    (0xFEEC, "Tile, Inc."),
+    (0xFEED, "Tile, Inc."),
+    (0xFEF5, "Dialog Semiconductor GmbH"),
+];
+
+static DECLARATIONS: &[(u16, &str)] = &[
+    (0x2800, "Primary Service"),
+    (0x2801, "Secondary Service"),
+    (0x2802, "Include"),
+    (0x2803, "Characteristic"),
+];
+
+static DESCRIPTORS: &[(u16, &str)] = &[
+    (0x2900, "Characteristic Extended Properties"),
+    (0x2901, "Characteristic User Description"),
+    (0x2902, "Client Characteristic Configuration"),
+    (0x2903, "Server Characteristic Configuration"),
+    (0x2904, "Characteristic Presentation Format"),
+    (0x2905, "Characteristic Aggregate Format"),
+    (0x2906, "Valid Range"),
+    (0x2907, "External Report Reference"),
+    (0x2908, "Report Reference"),
+    (0x2909, "Number of Digitals"),
+    (0x290A, "Value Trigger Setting"),
+    (0x290B, "Environmental Sensing Configuration"),
+    (0x290C, "Environmental Sensing Measurement"),
+    (0x290D, "Environmental Sensing Trigger Setting"),
+    (0x290E, "Time Trigger Setting"),
+    (0x290F, "Complete BR-EDR Transport Block Data"),
+];
+
+static CHARACTERISTICS: &[(u16, &str)] = &[
+    (0x2A00, "Device Name"),
+    (0x2A01, "Appearance"),
+    (0x2A02, "Peripheral Privacy Flag"),
+    (0x2A03, "Reconnection Address"),
+    (0x2A04, "Peripheral Preferred Connection Parameters"),
+    (0x2A05, "Service Changed"),
+    (0x2A06, "Alert Level"),
+    (0x2A07, "Tx Power Level"),
+    (0x2A08, "Date Time"),
+    (0x2A09, "Day of Week"),
+    (0x2A0A, "Day Date Time"),
+    (0x2A0C, "Exact Time 256"),
+    (0x2A0D, "DST Offset"),
+    (0x2A0E, "Time Zone"),
+    (0x2A0F, "Local Time Information"),
+    (0x2A11, "Time with DST"),
+    (0x2A12, "Time Accuracy"),
+    (0x2A13, "Time Source"),
+    (0x2A14, "Reference Time Information"),
+    (0x2A16, "Time Update Control Point"),
+    (0x2A17, "Time Update State"),
+    (0x2A18, "Glucose Measurement"),
+    (0x2A19, "Battery Level"),
+    (0x2A1C, "Temperature Measurement"),
+    (0x2A1D, "Temperature Type"),
+    (0x2A1E, "Intermediate Temperature"),
+    (0x2A21, "Measurement Interval"),
+    (0x2A22, "Boot Keyboard Input Report"),
+    (0x2A23, "System ID"),
+    (0x2A24, "Model Number String"),
+    (0x2A25, "Serial Number String"),
+    (0x2A26, "Firmware Revision String"),
+    (0x2A27, "Hardware Revision String"),
+    (0x2A28, "Software Revision String"),
+    (0x2A29, "Manufacturer Name String"),
+    (
+        0x2A2A,
+        "IEEE 11073-20601 Regulatory Certification Data List",
+    ),
+    (0x2A2B, "Current Time"),
+    (0x2A31, "Scan Refresh"),
+    (0x2A32, "Boot Keyboard Output Report"),
+    (0x2A33, "Boot Mouse Input Report"),
+    (0x2A34, "Glucose Measurement Context"),
+    (0x2A35, "Blood Pressure Measurement"),
+    (0x2A36, "Intermediate Cuff Pressure"),
+    (0x2A37, "Heart Rate Measurement"),
+    (0x2A38, "Body Sensor Location"),
+    (0x2A39, "Heart Rate Control Point"),
+    (0x2A3F, "Alert Status"),
+    (0x2A40, "Ringer Control Point"),
+    (0x2A41, "Ringer Setting"),
+    (0x2A42, "Alert Category ID Bit Mask"),
+    (0x2A43, "Alert Category ID"),
+    (0x2A44, "Alert Notification Control Point"),
+    (0x2A45, "Unread Alert Status"),
+    (0x2A46, "New Alert"),
+    (0x2A47, "Supported New Alert Category"),
+    (0x2A48, "Supported Unread Alert Category"),
+    (0x2A49, "Blood Pressure Feature"),
+    (0x2A4A, "HID Information"),
+    (0x2A4B, "Report Map"),
+    (0x2A4C, "HID Control Point"),
+    (0x2A4D, "Report"),
+    (0x2A4E, "Protocol Mode"),
+    (0x2A4F, "Scan Interval Window"),
+    (0x2A50, "PnP ID"),
+    (0x2A51, "Glucose Feature"),
+    (0x2A52, "Record Access Control Point"),
+    (0x2A53, "RSC Measurement"),
+    (0x2A54, "RSC Feature"),
+    (0x2A55, "SC Control Point"),
+    (0x2A5B, "CSC Measurement"),
+    (0x2A5C, "CSC Feature"),
+    (0x2A5D, "Sensor Location"),
+    (0x2A5E, "PLX Spot-Check Measurement"),
+    (0x2A5F, "PLX Continuous Measurement"),
+    (0x2A60, "PLX Features"),
+    (0x2A63, "Cycling Power Measurement"),
+    (0x2A64, "Cycling Power Vector"),
+    (0x2A65, "Cycling Power Feature"),
+    (0x2A66, "Cycling Power Control Point"),
+    (0x2A67, "Location and Speed"),
+    (0x2A68, "Navigation"),
+    (0x2A6C, "Elevation"),
+    (0x2A6D, "Pressure"),
+    (0x2A6E, "Temperature"),
+    (0x2A6F, "Humidity"),
+    (0x2A76, "UV Index"),
+    (0x2A7B, "Dew Point"),
+    (0x2A98, "Weight"),
+    (0x2A9D, "Weight Measurement"),
+    (0x2A9E, "Weight Scale Feature"),
+    (0x2AA6, "Central Address Resolution"),
+    (0x2ACC, "Fitness Machine Feature"),
+    (0x2AD2, "Indoor Bike Data"),
+    (0x2AD9, "Fitness Machine Control Point"),
+    (0x2B29, "Client Supported Features"),
+    (0x2B2A, "Database Hash"),
+    (0x2B3A, "Server Supported Features"),
+    (0x2B7D, "Volume State"),
+    (0x2B7E, "Volume Control Point"),
+    (0x2B7F, "Volume Flags"),
+];
+
+static COMPANIES: &[(u16, &str)] = &[
+    (0x0000, "Ericsson AB"),
+    (0x0001, "Nokia Mobile Phones"),
+    (0x0002, "Intel Corp."),
+    (0x0003, "IBM Corp."),
+    (0x0004, "Toshiba Corp."),
+    (0x0005, "3Com"),
+    (0x0006, "Microsoft"),
+    (0x0007, "Lucent"),
+    (0x0008, "Motorola"),
+    (0x0009, "Infineon Technologies AG"),
+    (0x000A, "Qualcomm Technologies International, Ltd. (QTIL)"),
+    (0x000D, "Texas Instruments Inc."),
+    (0x000F, "Broadcom Corporation"),
+    (0x0013, "Atmel Corporation"),
+    (0x001D, "Qualcomm"),
+    (0x0025, "NXP Semiconductors"),
+    (0x0030, "ST Microelectronics"),
+    (0x0036, "Renesas Electronics Corporation"),
+    (0x003A, "Panasonic Holdings Corporation"),
+    (0x003F, "Bluetooth SIG, Inc"),
+    (0x0046, "MediaTek, Inc."),
+    (0x0048, "Marvell Technology Group Ltd."),
+    (0x004C, "Apple, Inc."),
+    (0x0055, "Plantronics, Inc."),
+    (0x0057, "Harman International Industries, Inc."),
+    (0x0059, "Nordic Semiconductor ASA"),
+    (0x005D, "Realtek Semiconductor Corporation"),
+    (0x0065, "HP, Inc."),
+    (0x0067, "GN Audio A/S"),
+    (0x006B, "Polar Electro OY"),
+    (0x0075, "Samsung Electronics Co. Ltd."),
+    (0x0076, "Creative Technology Ltd."),
+    (0x0078, "Nike, Inc."),
+    (0x0082, "DSEA A/S"),
+    (0x0087, "Garmin International, Inc."),
+    (0x0089, "GN Hearing A/S"),
+    (0x008A, "Jawbone"),
+    (0x0094, "Airoha Technology Corp."),
+    (0x009E, "Bose Corporation"),
+    (0x009F, "Suunto Oy"),
+    (0x00A0, "Kensington Computer Products Group"),
+    (0x00C3, "adidas AG"),
+    (0x00C4, "LG Electronics"),
+    (0x00CC, "Beats Electronics"),
+    (0x00CD, "Microchip Technology Inc."),
+    (0x00D2, "Dialog Semiconductor B.V."),
+    (0x00D7, "Qualcomm Technologies, Inc."),
+    (0x00E0, "Google"),
+    (0x012D, "Sony Corporation"),
+    (0x0131, "Cypress Semiconductor"),
+    (0x0157, "Anhui Huami Information Technology Co., Ltd."),
+    (0x0171, "Amazon.com Services LLC"),
+    (0x01DA, "Logitech International SA"),
+    (0x0211, "Telink Semiconductor Co. Ltd"),
+    (0x022B, "Tesla, Inc."),
+    (0x027D, "HUAWEI Technologies Co., Ltd."),
+    (0x02E5, "Espressif Systems (Shanghai) Co., Ltd."),
+    (0x02FF, "Silicon Laboratories"),
+    (0x038F, "Xiaomi Inc."),
+    (0x0499, "Ruuvi Innovations Ltd."),
+    (0x0553, "Nintendo Co., Ltd."),
+    (0x0BA9, "Allterco Robotics ltd"),
+];
+
+static APPEARANCE_CATEGORIES: &[(u16, &str)] = &[
+    (0x000, "Unknown"),
+    (0x001, "Phone"),
+    (0x002, "Computer"),
+    (0x003, "Watch"),
+    (0x004, "Clock"),
+    (0x005, "Display"),
+    (0x006, "Remote Control"),
+    (0x007, "Eye-glasses"),
+    (0x008, "Tag"),
+    (0x009, "Keyring"),
+    (0x00A, "Media Player"),
+    (0x00B, "Barcode Scanner"),
+    (0x00C, "Thermometer"),
+    (0x00D, "Heart Rate Sensor"),
+    (0x00E, "Blood Pressure"),
+    (0x00F, "Human Interface Device"),
+    (0x010, "Glucose Meter"),
+    (0x011, "Running Walking Sensor"),
+    (0x012, "Cycling"),
+    (0x013, "Control Device"),
+    (0x014, "Network Device"),
+    (0x015, "Sensor"),
+    (0x016, "Light Fixtures"),
+    (0x017, "Fan"),
+    (0x018, "HVAC"),
+    (0x019, "Air Conditioning"),
+    (0x01A, "Humidifier"),
+    (0x01B, "Heating"),
+    (0x01C, "Access Control"),
+    (0x01D, "Motorized Device"),
+    (0x01E, "Power Device"),
+    (0x01F, "Light Source"),
+    (0x020, "Window Covering"),
+    (0x021, "Audio Sink"),
+    (0x022, "Audio Source"),
+    (0x023, "Motorized Vehicle"),
+    (0x024, "Domestic Appliance"),
+    (0x025, "Wearable Audio Device"),
+    (0x026, "Aircraft"),
+    (0x027, "AV Equipment"),
+    (0x028, "Display Equipment"),
+    (0x029, "Hearing aid"),
+    (0x02A, "Gaming"),
+    (0x02B, "Signage"),
+    (0x031, "Pulse Oximeter"),
+    (0x032, "Weight Scale"),
+    (0x033, "Personal Mobility Device"),
+    (0x034, "Continuous Glucose Monitor"),
+    (0x035, "Insulin Pump"),
+    (0x036, "Medication Delivery"),
+    (0x037, "Spirometer"),
+    (0x051, "Outdoor Sports Activity"),
+];
+
+/// Appearance subcategories, keyed by the full 16 bit appearance value.
+static APPEARANCE_SUBCATEGORIES: &[(u16, &str)] = &[
+    (0x0081, "Desktop Workstation"),
+    (0x0082, "Server-class Computer"),
+    (0x0083, "Laptop"),
+    (0x0084, "Handheld PC/PDA"),
+    (0x0085, "Palm-size PC/PDA"),
+    (0x0086, "Wearable computer"),
+    (0x0087, "Tablet"),
+    (0x0088, "Docking Station"),
+    (0x0089, "All in One"),
+    (0x008A, "Blade Server"),
+    (0x008B, "Convertible"),
+    (0x008C, "Detachable"),
+    (0x008D, "IoT Gateway"),
+    (0x008E, "Mini PC"),
+    (0x008F, "Stick PC"),
+    (0x00C1, "Sports Watch"),
+    (0x00C2, "Smartwatch"),
+    (0x0301, "Ear Thermometer"),
+    (0x0341, "Heart Rate Belt"),
+    (0x0381, "Arm Blood Pressure"),
+    (0x0382, "Wrist Blood Pressure"),
+    (0x03C1, "Keyboard"),
+    (0x03C2, "Mouse"),
+    (0x03C3, "Joystick"),
+    (0x03C4, "Gamepad"),
+    (0x03C5, "Digitizer Tablet"),
+    (0x03C6, "Card Reader"),
+    (0x03C7, "Digital Pen"),
+    (0x03C8, "Barcode Scanner"),
+    (0x03C9, "Touchpad"),
+    (0x03CA, "Presentation Remote"),
+    (0x0441, "In-Shoe Running Walking Sensor"),
+    (0x0442, "On-Shoe Running Walking Sensor"),
+    (0x0443, "On-Hip Running Walking Sensor"),
+    (0x0481, "Cycling Computer"),
+    (0x0482, "Speed Sensor"),
+    (0x0483, "Cadence Sensor"),
+    (0x0484, "Power Sensor"),
+    (0x0485, "Speed and Cadence Sensor"),
+    (0x0541, "Motion Sensor"),
+    (0x0542, "Air quality Sensor"),
+    (0x0543, "Temperature Sensor"),
+    (0x0544, "Humidity Sensor"),
+    (0x0545, "Leak Sensor"),
+    (0x0546, "Smoke Sensor"),
+    (0x0547, "Occupancy Sensor"),
+    (0x0548, "Contact Sensor"),
+    (0x0549, "Carbon Monoxide Sensor"),
+    (0x054A, "Carbon Dioxide Sensor"),
+    (0x054B, "Ambient Light Sensor"),
+    (0x054C, "Energy Sensor"),
+    (0x054D, "Color Light Sensor"),
+    (0x054E, "Rain Sensor"),
+    (0x054F, "Fire Sensor"),
+    (0x0550, "Wind Sensor"),
+    (0x0551, "Proximity Sensor"),
+    (0x0552, "Multi-Sensor"),
+    (0x0841, "Standalone Speaker"),
+    (0x0842, "Soundbar"),
+    (0x0843, "Bookshelf Speaker"),
+    (0x0844, "Standmounted Speaker"),
+    (0x0845, "Speakerphone"),
+    (0x0881, "Microphone"),
+    (0x0882, "Alarm"),
+    (0x0883, "Bell"),
+    (0x0884, "Horn"),
+    (0x0885, "Broadcasting Device"),
+    (0x0941, "Earbud"),
+    (0x0942, "Headset"),
+    (0x0943, "Headphones"),
+    (0x0944, "Neck Band"),
+    (0x0A41, "In-ear hearing aid"),
+    (0x0A42, "Behind-ear hearing aid"),
+    (0x0A43, "Cochlear Implant"),
+    (0x0A81, "Home Video Game Console"),
+    (0x0A82, "Portable handheld console"),
+    (0x0C41, "Fingertip Pulse Oximeter"),
+    (0x0C42, "Wrist Worn Pulse Oximeter"),
+    (0x1441, "Location Display"),
+    (0x1442, "Location and Navigation Display"),
+    (0x1443, "Location Pod"),
+    (0x1444, "Location and Navigation Pod"),
+];
+
+fn lookup(table: &'static [(u16, &'static str)], key: u16) -> Option<&'static str> {
+    table
+        .binary_search_by_key(&key, |&(k, _)| k)
+        .ok()
+        .map(|idx| table[idx].1)
+}
+
+/// Long form of a 16 bit SIG assigned UUID.
+pub fn sig_uuid(short: u16) -> Uuid {
+    Uuid::from_u16(short)
+}
+
+/// Look up the name of a service, characteristic or descriptor UUID.
+///
+/// Custom names from the user's `uuids` file take precedence over SIG names.
+pub fn uuid_name(uuid: &Uuid) -> Option<String> {
+    if let Some(name) = CUSTOM_UUID_NAMES.get().and_then(|m| m.get(uuid)) {
+        return Some(name.clone());
+    }
+
+    let short = uuid.as_u16()?;
+
+    [SERVICES, DECLARATIONS, DESCRIPTORS, CHARACTERISTICS]
+        .into_iter()
+        .find_map(|table| lookup(table, short))
+        .map(|n| n.to_string())
+}
+
+/// Format a UUID for display, e.g. `Battery Service (0x180F)`.
+pub fn format_uuid(uuid: &Uuid) -> String {
+    let short = uuid
+        .as_u16()
+        .map_or_else(|| uuid.to_string(), |s| format!("0x{:04X}", s));
+
+    match uuid_name(uuid) {
+        Some(name) => format!("{} ({})", name, short),
+        None => short,
+    }
+}
+
+pub fn company_name(id: u16) -> Option<&'static str> {
+    lookup(COMPANIES, id)
+}
+
+/// Format a company identifier for display, e.g. `Apple, Inc. (0x004C)`.
+pub fn format_company(id: u16) -> String {
+    format!("{} (0x{:04X})", company_name(id).unwrap_or("Unknown"), id)
+}
+
+/// Name of the category encoded in the top 10 bits of an appearance value.
+pub fn appearance_category_name(appearance: u16) -> Option<&'static str> {
+    lookup(APPEARANCE_CATEGORIES, appearance >> 6)
+}
+
+/// Name of the subcategory encoded in the bottom 6 bits of an appearance value.
+pub fn appearance_subcategory_name(appearance: u16) -> Option<&'static str> {
+    lookup(APPEARANCE_SUBCATEGORIES, appearance)
+}
+
+/// Format an appearance value for display, e.g. `Watch: Smartwatch (0x00C2)`.
+pub fn format_appearance(appearance: u16) -> String {
+    let name = match (
+        appearance_category_name(appearance),
+        appearance_subcategory_name(appearance),
+    ) {
+        (Some(category), Some(subcategory)) => format!("{}: {}", category, subcategory),
+        (Some(category), None) => category.to_string(),
+        _ => "Unknown".to_string(),
+    };
+
+    format!("{} (0x{:04X})", name, appearance)
+}
+
+/// Load user defined UUID names from `$XDG_CONFIG_HOME/bluetool/uuids`.
+///
+/// Each non-empty line holds a UUID, either in full or as a 16 bit short form such as
+/// `0xFFF0`, followed by whitespace and the name to display for it. Lines starting with
+/// `#` are ignored.
+pub fn load_custom_uuid_names() -> Result<()> {
+    let Some(path) = config_dir().map(|d| d.join("uuids")) else {
+        return Ok(());
+    };
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+
+    let names = parse_uuid_names(&contents, &path.display().to_string());
+    let _ = CUSTOM_UUID_NAMES.set(names);
+
+    Ok(())
+}
+
+/// Parse the contents of a `uuids` file, skipping lines that can't be parsed with a
+/// warning in the log so that one typo doesn't cost the user the rest of their names.
+fn parse_uuid_names(contents: &str, source: &str) -> HashMap<Uuid, String> {
+    let mut names = HashMap::new();
+
+    for (lineno, line) in contents.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let Some((uuid, name)) = line
+            .split_once(char::is_whitespace)
+            .map(|(u, n)| (u, n.trim()))
+            .filter(|(_, n)| !n.is_empty())
+        else {
+            log::warn!(
+                "{}:{}: expected `<uuid> <name>`, skipping line",
+                source,
+                lineno + 1
+            );
+            continue;
+        };
+
+        let Some(uuid) = parse_uuid(uuid) else {
+            log::warn!(
+                "{}:{}: invalid UUID `{}`, skipping line",
+                source,
+                lineno + 1,
+                uuid
+            );
+            continue;
+        };
+
+        names.insert(uuid, name.to_string());
+    }
+
+    names
+}
+
+fn parse_uuid(s: &str) -> Option<Uuid> {
+    let hex = s.trim_start_matches("0x").trim_start_matches("0X");
+
+    if hex.len() <= 4 {
+        u16::from_str_radix(hex, 16).ok().map(sig_uuid)
+    } else if hex.len() <= 8 && !hex.contains('-') {
+        u32::from_str_radix(hex, 16).ok().map(Uuid::from_u32)
+    } else {
+        s.parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_sorted(name: &str, table: &[(u16, &str)]) {
+        for pair in table.windows(2) {
+            assert!(
+                pair[0].0 < pair[1].0,
+                "{} is not sorted: 0x{:04X} comes before 0x{:04X}",
+                name,
+                pair[0].0,
+                pair[1].0
+            );
+        }
+    }
+
+    #[test]
+    fn tables_are_sorted() {
+        assert_sorted("SERVICES", SERVICES);
+        assert_sorted("DECLARATIONS", DECLARATIONS);
+        assert_sorted("DESCRIPTORS", DESCRIPTORS);
+        assert_sorted("CHARACTERISTICS", CHARACTERISTICS);
+        assert_sorted("COMPANIES", COMPANIES);
+        assert_sorted("APPEARANCE_CATEGORIES", APPEARANCE_CATEGORIES);
+        assert_sorted("APPEARANCE_SUBCATEGORIES", APPEARANCE_SUBCATEGORIES);
+    }
+
+    #[test]
+    fn parse_uuid_forms() {
+        assert_eq!(parse_uuid("0xFFF0"), Some(sig_uuid(0xFFF0)));
+        assert_eq!(parse_uuid("180f"), Some(sig_uuid(0x180F)));
+        assert_eq!(parse_uuid("0x0000FEAA"), Some(Uuid::from_u32(0xFEAA)));
+        assert_eq!(parse_uuid("0x12345678"), Some(Uuid::from_u32(0x1234_5678)));
+        assert_eq!(
+            parse_uuid("6e400001-b5a3-f393-e0a9-e50e24dcca9e"),
+            "6e400001-b5a3-f393-e0a9-e50e24dcca9e".parse().ok()
+        );
+
+        assert_eq!(parse_uuid("0xZZZZ"), None);
+        assert_eq!(parse_uuid("not-a-uuid-at-all"), None);
+        assert_eq!(parse_uuid(""), None);
+    }
+
+    #[test]
+    fn parse_uuid_names_skips_bad_lines() {
+        let names = parse_uuid_names(
+            "# comment\n\n0xFFF0 My Sensor\nnonsense\n0xZZZZ Broken\n0xFFF1   Other  \n",
+            "uuids",
+        );
+
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[&sig_uuid(0xFFF0)], "My Sensor");
+        assert_eq!(names[&sig_uuid(0xFFF1)], "Other");
+    }
+
+    #[test]
+    fn format_uuid_names() {
+        assert_eq!(
+            format_uuid(&sig_uuid(BATTERY_SERVICE)),
+            "Battery Service (0x180F)"
+        );
+        assert_eq!(
+            format_uuid(&sig_uuid(BATTERY_LEVEL)),
+            "Battery Level (0x2A19)"
+        );
+        assert_eq!(format_uuid(&sig_uuid(0xFFF0)), "0xFFF0");
+
+        let long: Uuid = "6e400001-b5a3-f393-e0a9-e50e24dcca9e".parse().unwrap();
+        assert_eq!(format_uuid(&long), "6e400001-b5a3-f393-e0a9-e50e24dcca9e");
+    }
+
+    #[test]
+    fn lookups() {
+        assert_eq!(company_name(0x004C), Some("Apple, Inc."));
+        assert_eq!(company_name(0xFFFE), None);
+        assert_eq!(format_company(0xFFFE), "Unknown (0xFFFE)");
+
+        assert_eq!(appearance_category_name(0x00C2), Some("Watch"));
+        assert_eq!(appearance_subcategory_name(0x00C2), Some("Smartwatch"));
+        assert_eq!(format_appearance(0x00C2), "Watch: Smartwatch (0x00C2)");
+        assert_eq!(format_appearance(0x00C0), "Watch (0x00C0)");
+        assert_eq!(format_appearance(0xFFC0), "Unknown (0xFFC0)");
+    }
+}
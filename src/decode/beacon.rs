@@ -0,0 +1,123 @@
+//! Structured parsing of the iBeacon and Eddystone-UID beacon frames.
+
+use bluer::Uuid;
+
+const IBEACON_TYPE: u8 = 0x02;
+const EDDYSTONE_UID_FRAME: u8 = 0x00;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IBeacon {
+    pub uuid: Uuid,
+    pub major: u16,
+    pub minor: u16,
+    /// Calibrated RSSI at 1m.
+    pub tx_power: i8,
+}
+
+impl IBeacon {
+    /// Parse an iBeacon from Apple's manufacturer data.
+    pub fn from_manufacturer_data(data: &[u8]) -> Option<Self> {
+        match data {
+            [IBEACON_TYPE, len, payload @ ..] => Self::from_payload(payload.get(..*len as usize)?),
+            _ => None,
+        }
+    }
+
+    pub fn from_payload(payload: &[u8]) -> Option<Self> {
+        if payload.len() < 21 {
+            return None;
+        }
+
+        Some(IBeacon {
+            uuid: Uuid::from_bytes(payload[0..16].try_into().ok()?),
+            major: u16::from_be_bytes([payload[16], payload[17]]),
+            minor: u16::from_be_bytes([payload[18], payload[19]]),
+            tx_power: payload[20] as i8,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EddystoneUid {
+    pub namespace: [u8; 10],
+    pub instance: [u8; 6],
+    /// Calibrated RSSI at 0m.
+    pub tx_power: i8,
+}
+
+impl EddystoneUid {
+    /// Parse an Eddystone-UID frame from Eddystone service data.
+    pub fn from_service_data(data: &[u8]) -> Option<Self> {
+        match data {
+            [EDDYSTONE_UID_FRAME, tx_power, rest @ ..] if rest.len() >= 16 => Some(EddystoneUid {
+                namespace: rest[0..10].try_into().ok()?,
+                instance: rest[10..16].try_into().ok()?,
+                tx_power: *tx_power as i8,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Apple's manufacturer data for an iBeacon, as broadcast by AirLocate.
+    const IBEACON: [u8; 23] = [
+        0x02, 0x15, 0xE2, 0xC5, 0x6D, 0xB5, 0xDF, 0xFB, 0x48, 0xD2, 0xB0, 0x60, 0xD0, 0xF5, 0xA7,
+        0x10, 0x96, 0xE0, 0x00, 0x01, 0x00, 0x02, 0xC5,
+    ];
+
+    /// Eddystone service data for a UID frame, with the reserved bytes on the end.
+    const EDDYSTONE_UID: [u8; 20] = [
+        0x00, 0xE7, 0xED, 0xD1, 0xEB, 0xEA, 0xC0, 0x4E, 0x5D, 0xEF, 0xA0, 0x17, 0x01, 0x02, 0x03,
+        0x04, 0x05, 0x06, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn ibeacon() {
+        let beacon = IBeacon::from_manufacturer_data(&IBEACON).unwrap();
+
+        assert_eq!(
+            beacon.uuid,
+            "e2c56db5-dffb-48d2-b060-d0f5a71096e0"
+                .parse::<Uuid>()
+                .unwrap()
+        );
+        assert_eq!(beacon.major, 1);
+        assert_eq!(beacon.minor, 2);
+        assert_eq!(beacon.tx_power, -59);
+    }
+
+    #[test]
+    fn eddystone_uid() {
+        let beacon = EddystoneUid::from_service_data(&EDDYSTONE_UID).unwrap();
+
+        assert_eq!(
+            beacon.namespace,
+            [0xED, 0xD1, 0xEB, 0xEA, 0xC0, 0x4E, 0x5D, 0xEF, 0xA0, 0x17]
+        );
+        assert_eq!(beacon.instance, [0x01, 0x02, 0x03, 0x04, 0x05, 0x06]);
+        assert_eq!(beacon.tx_power, -25);
+    }
+
+    #[test]
+    fn other_frames() {
+        // Proximity pairing rather than iBeacon, and Eddystone-URL rather than UID
+        assert_eq!(IBeacon::from_manufacturer_data(&[0x07, 0x01, 0x01]), None);
+        assert_eq!(
+            EddystoneUid::from_service_data(&[0x10, 0xEB, 0x03, b'a', b'b', b'c']),
+            None
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        assert_eq!(IBeacon::from_manufacturer_data(&[]), None);
+        assert_eq!(IBeacon::from_manufacturer_data(&IBEACON[..22]), None);
+        assert_eq!(IBeacon::from_payload(&IBEACON[2..22]), None);
+        assert_eq!(EddystoneUid::from_service_data(&[]), None);
+        assert_eq!(EddystoneUid::from_service_data(&EDDYSTONE_UID[..17]), None);
+    }
+}
@@ -0,0 +1,303 @@
+//! Decode the 24 bit BR/EDR Class of Device.
+//!
+//! Bits 2-7 hold the minor class, bits 8-12 the major class and bits 13-23 the
+//! service class flags.
+
+use std::fmt;
+
+static SERVICE_CLASSES: [(u32, &str); 9] = [
+    (13, "Limited Discoverable"),
+    (16, "Positioning"),
+    (17, "Networking"),
+    (18, "Rendering"),
+    (19, "Capturing"),
+    (20, "Object Transfer"),
+    (21, "Audio"),
+    (22, "Telephony"),
+    (23, "Information"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceClass(pub u32);
+
+impl DeviceClass {
+    fn major_bits(&self) -> u32 {
+        (self.0 >> 8) & 0x1F
+    }
+
+    fn minor_bits(&self) -> u32 {
+        (self.0 >> 2) & 0x3F
+    }
+
+    pub fn major(&self) -> &'static str {
+        match self.major_bits() {
+            0x00 => "Miscellaneous",
+            0x01 => "Computer",
+            0x02 => "Phone",
+            0x03 => "Network Access Point",
+            0x04 => "Audio/Video",
+            0x05 => "Peripheral",
+            0x06 => "Imaging",
+            0x07 => "Wearable",
+            0x08 => "Toy",
+            0x09 => "Health",
+            0x1F => "Uncategorized",
+            _ => "Reserved",
+        }
+    }
+
+    pub fn minor(&self) -> String {
+        let minor = self.minor_bits();
+
+        let name = match self.major_bits() {
+            0x01 => match minor {
+                1 => "Desktop Workstation",
+                2 => "Server-class Computer",
+                3 => "Laptop",
+                4 => "Handheld PC/PDA",
+                5 => "Palm-size PC/PDA",
+                6 => "Wearable Computer",
+                7 => "Tablet",
+                _ => "Uncategorized",
+            },
+            0x02 => match minor {
+                1 => "Cellular",
+                2 => "Cordless",
+                3 => "Smartphone",
+                4 => "Wired Modem or Voice Gateway",
+                5 => "Common ISDN Access",
+                _ => "Uncategorized",
+            },
+            0x03 => match minor >> 3 {
+                0 => "Fully Available",
+                1 => "1-17% Utilized",
+                2 => "17-33% Utilized",
+                3 => "33-50% Utilized",
+                4 => "50-67% Utilized",
+                5 => "67-83% Utilized",
+                6 => "83-99% Utilized",
+                _ => "No Service Available",
+            },
+            0x04 => match minor {
+                1 => "Wearable Headset",
+                2 => "Hands-free",
+                4 => "Microphone",
+                5 => "Loudspeaker",
+                6 => "Headphones",
+                7 => "Portable Audio",
+                8 => "Car Audio",
+                9 => "Set-top Box",
+                10 => "HiFi Audio",
+                11 => "VCR",
+                12 => "Video Camera",
+                13 => "Camcorder",
+                14 => "Video Monitor",
+                15 => "Video Display and Loudspeaker",
+                16 => "Video Conferencing",
+                18 => "Gaming/Toy",
+                _ => "Uncategorized",
+            },
+            0x05 => return self.peripheral_minor(),
+            0x06 => return self.imaging_minor(),
+            0x07 => match minor {
+                1 => "Wristwatch",
+                2 => "Pager",
+                3 => "Jacket",
+                4 => "Helmet",
+                5 => "Glasses",
+                6 => "Pin",
+                _ => "Uncategorized",
+            },
+            0x08 => match minor {
+                1 => "Robot",
+                2 => "Vehicle",
+                3 => "Doll/Action Figure",
+                4 => "Controller",
+                5 => "Game",
+                _ => "Uncategorized",
+            },
+            0x09 => match minor {
+                1 => "Blood Pressure Monitor",
+                2 => "Thermometer",
+                3 => "Weighing Scale",
+                4 => "Glucose Meter",
+                5 => "Pulse Oximeter",
+                6 => "Heart/Pulse Rate Monitor",
+                7 => "Health Data Display",
+                8 => "Step Counter",
+                9 => "Body Composition Analyzer",
+                10 => "Peak Flow Monitor",
+                11 => "Medication Monitor",
+                12 => "Knee Prosthesis",
+                13 => "Ankle Prosthesis",
+                14 => "Generic Health Manager",
+                15 => "Personal Mobility Device",
+                _ => "Uncategorized",
+            },
+            _ => "Uncategorized",
+        };
+
+        name.to_string()
+    }
+
+    /// Peripherals encode a keyboard/pointer flag in the top two minor bits, and the
+    /// device type in the remaining four.
+    fn peripheral_minor(&self) -> String {
+        let minor = self.minor_bits();
+
+        let kind = match minor >> 4 {
+            1 => Some("Keyboard"),
+            2 => Some("Pointing Device"),
+            3 => Some("Combo Keyboard/Pointing Device"),
+            _ => None,
+        };
+
+        let device = match minor & 0x0F {
+            1 => Some("Joystick"),
+            2 => Some("Gamepad"),
+            3 => Some("Remote Control"),
+            4 => Some("Sensing Device"),
+            5 => Some("Digitizer Tablet"),
+            6 => Some("Card Reader"),
+            7 => Some("Digital Pen"),
+            8 => Some("Handheld Scanner"),
+            9 => Some("Handheld Gestural Input"),
+            _ => None,
+        };
+
+        match (kind, device) {
+            (Some(k), Some(d)) => format!("{} {}", k, d),
+            (Some(k), None) => k.to_string(),
+            (None, Some(d)) => d.to_string(),
+            (None, None) => "Uncategorized".to_string(),
+        }
+    }
+
+    /// Imaging devices set one flag bit per capability rather than using an enumeration.
+    fn imaging_minor(&self) -> String {
+        let minor = self.minor_bits();
+
+        let kinds = [
+            (2, "Display"),
+            (3, "Camera"),
+            (4, "Scanner"),
+            (5, "Printer"),
+        ]
+        .iter()
+        .filter(|(bit, _)| minor & (1 << bit) != 0)
+        .map(|(_, name)| *name)
+        .collect::<Vec<&str>>();
+
+        if kinds.is_empty() {
+            "Uncategorized".to_string()
+        } else {
+            kinds.join("/")
+        }
+    }
+
+    /// Names of the service class bits set on this device.
+    pub fn services(&self) -> Vec<&'static str> {
+        SERVICE_CLASSES
+            .iter()
+            .filter(|(bit, _)| self.0 & (1 << bit) != 0)
+            .map(|(_, name)| *name)
+            .collect()
+    }
+
+    /// The freedesktop icon name BlueZ would have assigned to this class of device.
+    pub fn icon_name(&self) -> Option<&'static str> {
+        let minor = self.minor_bits();
+
+        match self.major_bits() {
+            0x01 => Some("computer"),
+            0x02 => Some("phone"),
+            0x03 => Some("network-wireless"),
+            0x04 => match minor {
+                1 | 2 => Some("audio-headset"),
+                6 => Some("audio-headphones"),
+                5 | 7 | 8 | 10 => Some("audio-speakers"),
+                12 | 13 => Some("camera-video"),
+                14 | 15 => Some("video-display"),
+                18 => Some("input-gaming"),
+                _ => Some("audio-card"),
+            },
+            0x05 => match minor >> 4 {
+                1 => Some("input-keyboard"),
+                2 => Some("input-mouse"),
+                _ => match minor & 0x0F {
+                    1 | 2 => Some("input-gaming"),
+                    5 => Some("input-tablet"),
+                    _ => None,
+                },
+            },
+            0x06 => {
+                if minor & (1 << 5) != 0 {
+                    Some("printer")
+                } else if minor & (1 << 3) != 0 {
+                    Some("camera-photo")
+                } else {
+                    None
+                }
+            }
+            0x08 if minor == 4 => Some("input-gaming"),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DeviceClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} (0x{:06X})", self.major(), self.minor(), self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headphones() {
+        let class = DeviceClass(2360344);
+
+        assert_eq!(class.to_string(), "Audio/Video: Headphones (0x240418)");
+        assert_eq!(class.services(), ["Rendering", "Audio"]);
+        assert_eq!(class.icon_name(), Some("audio-headphones"));
+    }
+
+    #[test]
+    fn peripherals() {
+        let keyboard = DeviceClass(0x002540);
+        assert_eq!(keyboard.major(), "Peripheral");
+        assert_eq!(keyboard.minor(), "Keyboard");
+        assert_eq!(keyboard.services(), ["Limited Discoverable"]);
+        assert_eq!(keyboard.icon_name(), Some("input-keyboard"));
+
+        let gamepad = DeviceClass(0x000508);
+        assert_eq!(gamepad.minor(), "Gamepad");
+        assert_eq!(gamepad.icon_name(), Some("input-gaming"));
+
+        assert_eq!(
+            DeviceClass(0x0005C0).minor(),
+            "Combo Keyboard/Pointing Device"
+        );
+    }
+
+    #[test]
+    fn imaging_flags() {
+        let printer = DeviceClass(0x040680);
+
+        assert_eq!(printer.to_string(), "Imaging: Printer (0x040680)");
+        assert_eq!(printer.icon_name(), Some("printer"));
+        assert_eq!(DeviceClass(0x0006A0).minor(), "Camera/Printer");
+    }
+
+    #[test]
+    fn empty() {
+        let class = DeviceClass(0);
+
+        assert_eq!(class.to_string(), "Miscellaneous: Uncategorized (0x000000)");
+        assert!(class.services().is_empty());
+        assert_eq!(class.icon_name(), None);
+        assert_eq!(DeviceClass(0x1F00).major(), "Uncategorized");
+    }
+}
@@ -0,0 +1,53 @@
+/// Human readable fields decoded from an advertisement payload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoded {
+    /// Name of the payload format, e.g. `iBeacon`.
+    pub format: String,
+    pub fields: Vec<(String, String)>,
+}
+
+impl Decoded {
+    pub fn new(format: &str) -> Self {
+        Decoded {
+            format: format.to_string(),
+            fields: vec![],
+        }
+    }
+
+    pub fn field(mut self, name: &str, value: impl ToString) -> Self {
+        self.fields.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// Format bytes as lines of a classic hex dump, 16 bytes per line.
+pub fn hex_dump(data: &[u8]) -> Vec<String> {
+    data.chunks(16)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex = chunk
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<Vec<String>>()
+                .join(" ");
+
+            let ascii = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>();
+
+            format!("{:04x}  {:<47}  |{}|", i * 16, hex, ascii)
+        })
+        .collect()
+}
+
+/// Format bytes as a contiguous lowercase hex string.
+pub fn hex_string(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,319 @@
+//! Decoders for manufacturer specific advertisement data, keyed by company identifier.
+//!
+//! BlueZ strips the company identifier before handing us the payload, so every decoder
+//! receives the bytes that follow it.
+
+use super::{
+    apple::ProximityPairing,
+    beacon::IBeacon,
+    decoded::{hex_string, Decoded},
+};
+
+type ManufacturerDecoder = fn(&[u8]) -> Vec<Decoded>;
+
+static DECODERS: [(u16, ManufacturerDecoder); 3] = [
+    (0x0006, decode_microsoft),
+    (0x004C, decode_apple),
+    (0x0499, decode_ruuvi),
+];
+
+/// Decode the manufacturer data advertised under `company`.
+///
+/// Returns an empty list when the company or payload format is unknown.
+pub fn decode_manufacturer_data(company: u16, data: &[u8]) -> Vec<Decoded> {
+    DECODERS
+        .iter()
+        .find(|(id, _)| *id == company)
+        .map_or_else(Vec::new, |(_, decoder)| decoder(data))
+}
+
+/// Apple packs one or more Continuity messages into its manufacturer data, each as a
+/// type, length and payload.
+fn decode_apple(data: &[u8]) -> Vec<Decoded> {
+    let mut messages = vec![];
+    let mut rest = data;
+
+    while let [msg_type, len, tail @ ..] = rest {
+        let len = *len as usize;
+
+        let Some(payload) = tail.get(..len) else {
+            break;
+        };
+
+        let decoded = match msg_type {
+            0x02 => Some(decode_ibeacon(payload)),
+            0x07 => ProximityPairing::from_payload(payload).map(|p| p.to_decoded()),
+            _ => None,
+        };
+
+        messages.push(decoded.unwrap_or_else(|| {
+            Decoded::new(&format!(
+                "Apple Continuity: {}",
+                apple_message_name(*msg_type)
+            ))
+            .field("Payload", hex_string(payload))
+        }));
+
+        rest = &tail[len..];
+    }
+
+    messages
+}
+
+fn apple_message_name(msg_type: u8) -> &'static str {
+    match msg_type {
+        0x03 => "AirPrint",
+        0x05 => "AirDrop",
+        0x06 => "HomeKit",
+        0x07 => "Proximity Pairing",
+        0x08 => "Hey Siri",
+        0x09 => "AirPlay Target",
+        0x0A => "AirPlay Source",
+        0x0B => "Magic Switch",
+        0x0C => "Handoff",
+        0x0D => "Tethering Target",
+        0x0E => "Tethering Source",
+        0x0F => "Nearby Action",
+        0x10 => "Nearby Info",
+        0x12 => "Find My",
+        _ => "Unknown",
+    }
+}
+
+fn decode_ibeacon(payload: &[u8]) -> Decoded {
+    match IBeacon::from_payload(payload) {
+        Some(beacon) => Decoded::new("iBeacon")
+            .field("UUID", beacon.uuid)
+            .field("Major", beacon.major)
+            .field("Minor", beacon.minor)
+            .field("TX power @ 1m", format!("{} dBm", beacon.tx_power)),
+        None => Decoded::new("iBeacon").field("Payload", hex_string(payload)),
+    }
+}
+
+/// Microsoft beacons start with a scenario identifier: `0x01` for Connected Devices
+/// Platform beacons, and `0x03` for Swift Pair.
+fn decode_microsoft(data: &[u8]) -> Vec<Decoded> {
+    let decoded = match data {
+        [0x03, sub_scenario, _rssi, rest @ ..] => {
+            let mut d = Decoded::new("Swift Pair").field(
+                "Mode",
+                match sub_scenario {
+                    0x00 => "LE only",
+                    0x01 => "LE and BR/EDR, same address",
+                    0x02 => "LE and BR/EDR, different address",
+                    _ => "Unknown",
+                },
+            );
+
+            // BR/EDR address and class of device precede the name when they differ from LE
+            let name = if *sub_scenario == 0x02 && rest.len() >= 9 {
+                let addr = rest[0..6]
+                    .iter()
+                    .rev()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<Vec<String>>()
+                    .join(":");
+                d = d.field("BR/EDR address", addr);
+                &rest[9..]
+            } else {
+                rest
+            };
+
+            if !name.is_empty() {
+                d = d.field("Display name", String::from_utf8_lossy(name));
+            }
+
+            d
+        }
+        [0x01, device_type, flags, _reserved, salt @ ..] if salt.len() >= 4 => {
+            Decoded::new("Connected Devices Platform")
+                .field("Device type", cdp_device_type(device_type & 0x1F))
+                .field("Version", device_type >> 5)
+                .field("Flags", format!("0x{:02x}", flags))
+                .field("Salt", hex_string(&salt[..4]))
+        }
+        _ => return vec![],
+    };
+
+    vec![decoded]
+}
+
+fn cdp_device_type(device_type: u8) -> &'static str {
+    match device_type {
+        1 => "Xbox One",
+        6 => "Apple iPhone",
+        7 => "Apple iPad",
+        8 => "Android device",
+        9 => "Windows 10 Desktop",
+        11 => "Windows 10 Phone",
+        12 => "Linux device",
+        13 => "Windows IoT",
+        14 => "Surface Hub",
+        15 => "Windows laptop",
+        16 => "Windows tablet",
+        _ => "Unknown",
+    }
+}
+
+/// Ruuvi tags advertise environmental readings in data format 3 (RAWv1) or 5 (RAWv2).
+fn decode_ruuvi(data: &[u8]) -> Vec<Decoded> {
+    let decoded = match data {
+        [0x05, ..] if data.len() >= 18 => {
+            let i16_at = |i: usize| i16::from_be_bytes([data[i], data[i + 1]]);
+            let u16_at = |i: usize| u16::from_be_bytes([data[i], data[i + 1]]);
+            let power = u16_at(13);
+
+            Decoded::new("Ruuvi RAWv2")
+                .field("Temperature", format!("{:.2} °C", i16_at(1) as f32 * 0.005))
+                .field("Humidity", format!("{:.2} %", u16_at(3) as f32 * 0.0025))
+                .field(
+                    "Pressure",
+                    format!("{:.2} hPa", (u16_at(5) as f32 + 50000.0) / 100.0),
+                )
+                .field(
+                    "Acceleration",
+                    format!(
+                        "x {} mG, y {} mG, z {} mG",
+                        i16_at(7),
+                        i16_at(9),
+                        i16_at(11)
+                    ),
+                )
+                .field("Battery", format!("{} mV", (power >> 5) + 1600))
+                .field(
+                    "TX power",
+                    format!("{} dBm", (power & 0x1F) as i16 * 2 - 40),
+                )
+                .field("Movements", data[15])
+                .field("Sequence", u16_at(16))
+        }
+        [0x03, ..] if data.len() >= 14 => {
+            let i16_at = |i: usize| i16::from_be_bytes([data[i], data[i + 1]]);
+            let u16_at = |i: usize| u16::from_be_bytes([data[i], data[i + 1]]);
+            let magnitude = (data[2] & 0x7F) as f32 + data[3] as f32 / 100.0;
+            let temperature = if data[2] & 0x80 != 0 {
+                -magnitude
+            } else {
+                magnitude
+            };
+
+            Decoded::new("Ruuvi RAWv1")
+                .field("Temperature", format!("{:.2} °C", temperature))
+                .field("Humidity", format!("{:.1} %", data[1] as f32 * 0.5))
+                .field(
+                    "Pressure",
+                    format!("{:.2} hPa", (u16_at(4) as f32 + 50000.0) / 100.0),
+                )
+                .field(
+                    "Acceleration",
+                    format!(
+                        "x {} mG, y {} mG, z {} mG",
+                        i16_at(6),
+                        i16_at(8),
+                        i16_at(10)
+                    ),
+                )
+                .field("Battery", format!("{} mV", u16_at(12)))
+        }
+        _ => return vec![],
+    };
+
+    vec![decoded]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(decoded: &Decoded) -> Vec<(&str, &str)> {
+        decoded
+            .fields
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn apple_ibeacon() {
+        let data = [
+            0x02, 0x15, 0xE2, 0xC5, 0x6D, 0xB5, 0xDF, 0xFB, 0x48, 0xD2, 0xB0, 0x60, 0xD0, 0xF5,
+            0xA7, 0x10, 0x96, 0xE0, 0x00, 0x01, 0x00, 0x02, 0xC5,
+        ];
+        let decoded = decode_manufacturer_data(0x004C, &data);
+
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].format, "iBeacon");
+        assert_eq!(
+            fields(&decoded[0]),
+            [
+                ("UUID", "e2c56db5-dffb-48d2-b060-d0f5a71096e0"),
+                ("Major", "1"),
+                ("Minor", "2"),
+                ("TX power @ 1m", "-59 dBm"),
+            ]
+        );
+    }
+
+    #[test]
+    fn apple_messages() {
+        // Nearby Info followed by a proximity pairing message too short to decode
+        let data = [0x10, 0x02, 0x0B, 0x1C, 0x07, 0x03, 0x01, 0x0E, 0x20];
+        let decoded = decode_manufacturer_data(0x004C, &data);
+
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].format, "Apple Continuity: Nearby Info");
+        assert_eq!(fields(&decoded[0]), [("Payload", "0b1c")]);
+        assert_eq!(decoded[1].format, "Apple Continuity: Proximity Pairing");
+        assert_eq!(fields(&decoded[1]), [("Payload", "010e20")]);
+    }
+
+    #[test]
+    fn swift_pair() {
+        let decoded = decode_manufacturer_data(0x0006, b"\x03\x00\x80Mouse");
+
+        assert_eq!(decoded[0].format, "Swift Pair");
+        assert_eq!(
+            fields(&decoded[0]),
+            [("Mode", "LE only"), ("Display name", "Mouse")]
+        );
+    }
+
+    #[test]
+    fn ruuvi_rawv2() {
+        // Valid data test vector from Ruuvi's data format 5 specification
+        let data = [
+            0x05, 0x12, 0xFC, 0x53, 0x94, 0xC3, 0x7C, 0x00, 0x04, 0xFF, 0xFC, 0x04, 0x0C, 0xAC,
+            0x36, 0x42, 0x00, 0xCD, 0xCB, 0xB8, 0x33, 0x4C, 0x88, 0x4F,
+        ];
+        let decoded = decode_manufacturer_data(0x0499, &data);
+
+        assert_eq!(
+            fields(&decoded[0]),
+            [
+                ("Temperature", "24.30 °C"),
+                ("Humidity", "53.49 %"),
+                ("Pressure", "1000.44 hPa"),
+                ("Acceleration", "x 4 mG, y -4 mG, z 1036 mG"),
+                ("Battery", "2977 mV"),
+                ("TX power", "4 dBm"),
+                ("Movements", "66"),
+                ("Sequence", "205"),
+            ]
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        for company in [0x0006, 0x004C, 0x0499, 0xFFFF] {
+            assert!(decode_manufacturer_data(company, &[]).is_empty());
+        }
+
+        // Length runs past the end of the data
+        assert!(decode_manufacturer_data(0x004C, &[0x02, 0x15, 0xE2, 0xC5]).is_empty());
+        assert!(decode_manufacturer_data(0x004C, &[0x02]).is_empty());
+        assert!(decode_manufacturer_data(0x0006, &[0x01, 0x09, 0x20, 0x00]).is_empty());
+        assert!(decode_manufacturer_data(0x0499, &[0x05, 0x12, 0xFC]).is_empty());
+        assert!(decode_manufacturer_data(0x0499, &[0x03, 0x29, 0x1A, 0x1E]).is_empty());
+    }
+}
@@ -0,0 +1,22 @@
+mod address;
+mod appearance;
+mod apple;
+mod assigned_numbers;
+mod beacon;
+mod class;
+mod decoded;
+mod manufacturer;
+mod service_data;
+
+pub use address::AddressKind;
+pub use appearance::Appearance;
+pub use apple::{ProximityPairing, APPLE_COMPANY_ID};
+pub use assigned_numbers::{
+    company_name, format_company, format_uuid, load_custom_uuid_names, sig_uuid, BATTERY_LEVEL,
+    BATTERY_SERVICE,
+};
+pub use beacon::{EddystoneUid, IBeacon};
+pub use class::DeviceClass;
+pub use decoded::{hex_dump, hex_string, Decoded};
+pub use manufacturer::decode_manufacturer_data;
+pub use service_data::decode_service_data;
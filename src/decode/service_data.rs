@@ -0,0 +1,500 @@
+//! Decoders for service data advertisements, keyed by 16 bit service UUID.
+
+use bluer::{Uuid, UuidExt};
+
+use super::{
+    beacon::EddystoneUid,
+    decoded::{hex_string, Decoded},
+};
+
+type ServiceDataDecoder = fn(&[u8]) -> Vec<Decoded>;
+
+static DECODERS: [(u16, ServiceDataDecoder); 6] = [
+    (0x181A, decode_environmental_sensing),
+    (0xFCD2, decode_bthome),
+    (0xFD6F, decode_exposure_notification),
+    (0xFE2C, decode_fast_pair),
+    (0xFE95, decode_mibeacon),
+    (0xFEAA, decode_eddystone),
+];
+
+/// (object id, name, size in bytes, signed, scale factor, unit)
+type BTHomeObject = (u8, &'static str, usize, bool, f64, &'static str);
+
+static BTHOME_OBJECTS: &[BTHomeObject] = &[
+    (0x00, "Packet ID", 1, false, 1.0, ""),
+    (0x01, "Battery", 1, false, 1.0, "%"),
+    (0x02, "Temperature", 2, true, 0.01, "°C"),
+    (0x03, "Humidity", 2, false, 0.01, "%"),
+    (0x04, "Pressure", 3, false, 0.01, "hPa"),
+    (0x05, "Illuminance", 3, false, 0.01, "lx"),
+    (0x06, "Mass", 2, false, 0.01, "kg"),
+    (0x07, "Mass", 2, false, 0.01, "lb"),
+    (0x08, "Dew point", 2, true, 0.01, "°C"),
+    (0x09, "Count", 1, false, 1.0, ""),
+    (0x0A, "Energy", 3, false, 0.001, "kWh"),
+    (0x0B, "Power", 3, false, 0.01, "W"),
+    (0x0C, "Voltage", 2, false, 0.001, "V"),
+    (0x0D, "PM2.5", 2, false, 1.0, "µg/m³"),
+    (0x0E, "PM10", 2, false, 1.0, "µg/m³"),
+    (0x0F, "Generic boolean", 1, false, 1.0, ""),
+    (0x10, "Power on", 1, false, 1.0, ""),
+    (0x11, "Opening", 1, false, 1.0, ""),
+    (0x12, "CO2", 2, false, 1.0, "ppm"),
+    (0x13, "TVOC", 2, false, 1.0, "µg/m³"),
+    (0x14, "Moisture", 2, false, 0.01, "%"),
+    (0x15, "Battery low", 1, false, 1.0, ""),
+    (0x16, "Battery charging", 1, false, 1.0, ""),
+    (0x1A, "Door", 1, false, 1.0, ""),
+    (0x20, "Moisture detected", 1, false, 1.0, ""),
+    (0x21, "Motion", 1, false, 1.0, ""),
+    (0x23, "Occupancy", 1, false, 1.0, ""),
+    (0x2D, "Window", 1, false, 1.0, ""),
+    (0x2E, "Humidity", 1, false, 1.0, "%"),
+    (0x2F, "Moisture", 1, false, 1.0, "%"),
+    (0x3A, "Button event", 1, false, 1.0, ""),
+    (0x3D, "Count", 2, false, 1.0, ""),
+    (0x3E, "Count", 4, false, 1.0, ""),
+    (0x3F, "Rotation", 2, true, 0.1, "°"),
+    (0x40, "Distance", 2, false, 1.0, "mm"),
+    (0x41, "Distance", 2, false, 0.1, "m"),
+    (0x43, "Current", 2, false, 0.001, "A"),
+    (0x45, "Temperature", 2, true, 0.1, "°C"),
+    (0x4A, "Voltage", 2, false, 0.1, "V"),
+    (0xF0, "Device type ID", 2, false, 1.0, ""),
+];
+
+/// Decode the service data advertised under `uuid`.
+///
+/// Returns an empty list when the service or payload format is unknown.
+pub fn decode_service_data(uuid: &Uuid, data: &[u8]) -> Vec<Decoded> {
+    let Some(short) = uuid.as_u16() else {
+        return vec![];
+    };
+
+    DECODERS
+        .iter()
+        .find(|(id, _)| *id == short)
+        .map_or_else(Vec::new, |(_, decoder)| decoder(data))
+}
+
+fn decode_eddystone(data: &[u8]) -> Vec<Decoded> {
+    let decoded = match data {
+        [0x00, ..] => match EddystoneUid::from_service_data(data) {
+            Some(uid) => Decoded::new("Eddystone-UID")
+                .field("Namespace", hex_string(&uid.namespace))
+                .field("Instance", hex_string(&uid.instance))
+                .field("TX power @ 0m", format!("{} dBm", uid.tx_power)),
+            None => return vec![],
+        },
+        [0x10, tx_power, scheme, url @ ..] => Decoded::new("Eddystone-URL")
+            .field("URL", eddystone_url(*scheme, url))
+            .field("TX power @ 0m", format!("{} dBm", *tx_power as i8)),
+        [0x20, 0x00, rest @ ..] if rest.len() >= 12 => {
+            let voltage = u16::from_be_bytes([rest[0], rest[1]]);
+            let temperature = i16::from_be_bytes([rest[2], rest[3]]) as f32 / 256.0;
+            let adv_count = u32::from_be_bytes([rest[4], rest[5], rest[6], rest[7]]);
+            let uptime = u32::from_be_bytes([rest[8], rest[9], rest[10], rest[11]]) / 10;
+
+            let mut d = Decoded::new("Eddystone-TLM");
+
+            if voltage != 0 {
+                d = d.field("Battery", format!("{} mV", voltage));
+            }
+
+            // 0x8000 means the beacon has no temperature sensor
+            if rest[2..4] != [0x80, 0x00] {
+                d = d.field("Temperature", format!("{:.2} °C", temperature));
+            }
+
+            d.field("Advertisements", adv_count).field(
+                "Uptime",
+                format!(
+                    "{}d {}h {}m",
+                    uptime / 86400,
+                    uptime % 86400 / 3600,
+                    uptime % 3600 / 60
+                ),
+            )
+        }
+        [0x20, 0x01, rest @ ..] => {
+            Decoded::new("Eddystone-TLM (encrypted)").field("Payload", hex_string(rest))
+        }
+        [0x30, tx_power, eid @ ..] if eid.len() >= 8 => Decoded::new("Eddystone-EID")
+            .field("Ephemeral ID", hex_string(&eid[..8]))
+            .field("TX power @ 0m", format!("{} dBm", *tx_power as i8)),
+        _ => return vec![],
+    };
+
+    vec![decoded]
+}
+
+fn eddystone_url(scheme: u8, encoded: &[u8]) -> String {
+    let mut url = match scheme {
+        0x00 => "http://www.",
+        0x01 => "https://www.",
+        0x02 => "http://",
+        0x03 => "https://",
+        _ => "",
+    }
+    .to_string();
+
+    for &b in encoded {
+        match b {
+            0x00 => url.push_str(".com/"),
+            0x01 => url.push_str(".org/"),
+            0x02 => url.push_str(".edu/"),
+            0x03 => url.push_str(".net/"),
+            0x04 => url.push_str(".info/"),
+            0x05 => url.push_str(".biz/"),
+            0x06 => url.push_str(".gov/"),
+            0x07 => url.push_str(".com"),
+            0x08 => url.push_str(".org"),
+            0x09 => url.push_str(".edu"),
+            0x0A => url.push_str(".net"),
+            0x0B => url.push_str(".info"),
+            0x0C => url.push_str(".biz"),
+            0x0D => url.push_str(".gov"),
+            _ => url.push(b as char),
+        }
+    }
+
+    url
+}
+
+/// The SIG doesn't define an advertisement format for Environmental Sensing service
+/// data, but the ATC and pvvx firmwares for cheap thermometers use it, distinguishable by
+/// length.
+fn decode_environmental_sensing(data: &[u8]) -> Vec<Decoded> {
+    let decoded = match data.len() {
+        13 => Decoded::new("ATC thermometer")
+            .field(
+                "Temperature",
+                format!(
+                    "{:.1} °C",
+                    i16::from_be_bytes([data[6], data[7]]) as f32 / 10.0
+                ),
+            )
+            .field("Humidity", format!("{} %", data[8]))
+            .field("Battery", format!("{} %", data[9]))
+            .field(
+                "Battery voltage",
+                format!("{} mV", u16::from_be_bytes([data[10], data[11]])),
+            ),
+        15 => Decoded::new("pvvx thermometer")
+            .field(
+                "Temperature",
+                format!(
+                    "{:.2} °C",
+                    i16::from_le_bytes([data[6], data[7]]) as f32 / 100.0
+                ),
+            )
+            .field(
+                "Humidity",
+                format!(
+                    "{:.2} %",
+                    u16::from_le_bytes([data[8], data[9]]) as f32 / 100.0
+                ),
+            )
+            .field("Battery", format!("{} %", data[12]))
+            .field(
+                "Battery voltage",
+                format!("{} mV", u16::from_le_bytes([data[10], data[11]])),
+            ),
+        _ => return vec![],
+    };
+
+    vec![decoded]
+}
+
+fn decode_fast_pair(data: &[u8]) -> Vec<Decoded> {
+    let decoded = match data {
+        [a, b, c] => Decoded::new("Google Fast Pair")
+            .field("Mode", "Discoverable")
+            .field("Model ID", format!("0x{:02X}{:02X}{:02X}", a, b, c)),
+        [_flags, rest @ ..] => {
+            let mut d = Decoded::new("Google Fast Pair").field("Mode", "Not discoverable");
+
+            // The account key filter is a length/type header followed by a bloom filter
+            if let [header, ..] = rest {
+                d = d
+                    .field("Account key filter", format!("{} bytes", header >> 4))
+                    .field(
+                        "Show pairing UI",
+                        if header & 0x0F == 0 { "Yes" } else { "No" },
+                    );
+            }
+
+            d
+        }
+        _ => return vec![],
+    };
+
+    vec![decoded]
+}
+
+fn decode_exposure_notification(data: &[u8]) -> Vec<Decoded> {
+    if data.len() < 20 {
+        return vec![];
+    }
+
+    vec![Decoded::new("Exposure Notification")
+        .field("Rolling proximity ID", hex_string(&data[0..16]))
+        .field("Encrypted metadata", hex_string(&data[16..20]))]
+}
+
+fn decode_bthome(data: &[u8]) -> Vec<Decoded> {
+    let [device_info, objects @ ..] = data else {
+        return vec![];
+    };
+
+    let mut d = Decoded::new(&format!("BTHome v{}", device_info >> 5));
+
+    if device_info & 0x01 != 0 {
+        return vec![d.field("Payload", format!("Encrypted, {}", hex_string(objects)))];
+    }
+
+    if device_info & 0x04 != 0 {
+        d = d.field("Trigger based", "Yes");
+    }
+
+    let mut rest = objects;
+
+    while let [id, tail @ ..] = rest {
+        let Some(&(_, name, size, signed, factor, unit)) =
+            BTHOME_OBJECTS.iter().find(|(oid, ..)| oid == id)
+        else {
+            // Object sizes are implied by their id, so we can't skip past unknown ones
+            d = d.field("Undecoded", hex_string(rest));
+            break;
+        };
+
+        let Some(bytes) = tail.get(..size) else {
+            break;
+        };
+
+        let value = le_value(bytes, signed) as f64 * factor;
+        let precision = (-factor.log10()).max(0.0) as usize;
+
+        d = d.field(name, format!("{:.*} {}", precision, value, unit).trim_end());
+
+        rest = &tail[size..];
+    }
+
+    vec![d]
+}
+
+fn le_value(bytes: &[u8], signed: bool) -> i64 {
+    let unsigned = bytes
+        .iter()
+        .rev()
+        .fold(0u64, |acc, &b| (acc << 8) | b as u64);
+
+    if signed {
+        let shift = 64 - 8 * bytes.len();
+        ((unsigned << shift) as i64) >> shift
+    } else {
+        unsigned as i64
+    }
+}
+
+/// Xiaomi MiBeacon frames carry a frame control word, product and counter, followed by
+/// optional MAC, capability and object fields as flagged in the frame control.
+fn decode_mibeacon(data: &[u8]) -> Vec<Decoded> {
+    if data.len() < 5 {
+        return vec![];
+    }
+
+    let frame_control = u16::from_le_bytes([data[0], data[1]]);
+    let product_id = u16::from_le_bytes([data[2], data[3]]);
+
+    let mut d = Decoded::new("Xiaomi MiBeacon")
+        .field("Product ID", format!("0x{:04X}", product_id))
+        .field("Frame counter", data[4]);
+
+    if frame_control & 0x0008 != 0 {
+        return vec![d.field("Objects", "Encrypted")];
+    }
+
+    let mut offset = 5;
+
+    if frame_control & 0x0010 != 0 {
+        offset += 6;
+    }
+
+    if frame_control & 0x0020 != 0 {
+        // Capability byte, with an extra IO capability word when flagged
+        let capability = data.get(offset).copied().unwrap_or(0);
+        offset += if capability & 0x20 != 0 { 3 } else { 1 };
+    }
+
+    if frame_control & 0x0040 == 0 {
+        return vec![d];
+    }
+
+    let mut rest = data.get(offset..).unwrap_or_default();
+
+    while let [t0, t1, len, tail @ ..] = rest {
+        let len = *len as usize;
+
+        let Some(value) = tail.get(..len) else {
+            break;
+        };
+
+        let i16_at = |i: usize| i16::from_le_bytes([value[i], value[i + 1]]) as f32 / 10.0;
+
+        d = match (u16::from_le_bytes([*t0, *t1]), len) {
+            (0x1004, 2) => d.field("Temperature", format!("{:.1} °C", i16_at(0))),
+            (0x1006, 2) => d.field("Humidity", format!("{:.1} %", i16_at(0))),
+            (0x100A, 1) => d.field("Battery", format!("{} %", value[0])),
+            (0x100D, 4) => d
+                .field("Temperature", format!("{:.1} °C", i16_at(0)))
+                .field("Humidity", format!("{:.1} %", i16_at(2))),
+            (object, _) => d.field(&format!("Object 0x{:04X}", object), hex_string(value)),
+        };
+
+        rest = &tail[len..];
+    }
+
+    vec![d]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(service: u16, data: &[u8]) -> Vec<(String, Vec<(String, String)>)> {
+        decode_service_data(&Uuid::from_u16(service), data)
+            .into_iter()
+            .map(|d| (d.format, d.fields))
+            .collect()
+    }
+
+    fn decoded(format: &str, fields: &[(&str, &str)]) -> Vec<(String, Vec<(String, String)>)> {
+        vec![(
+            format.to_string(),
+            fields
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        )]
+    }
+
+    #[test]
+    fn eddystone_url() {
+        let data = b"\x10\xEB\x03google\x07";
+
+        assert_eq!(
+            decode(0xFEAA, data),
+            decoded(
+                "Eddystone-URL",
+                &[("URL", "https://google.com"), ("TX power @ 0m", "-21 dBm")]
+            )
+        );
+    }
+
+    #[test]
+    fn eddystone_tlm() {
+        let data = [
+            0x20, 0x00, 0x0B, 0xB8, 0x18, 0x80, 0x00, 0x00, 0x27, 0x10, 0x00, 0x0D, 0x2F, 0x00,
+        ];
+
+        assert_eq!(
+            decode(0xFEAA, &data),
+            decoded(
+                "Eddystone-TLM",
+                &[
+                    ("Battery", "3000 mV"),
+                    ("Temperature", "24.50 °C"),
+                    ("Advertisements", "10000"),
+                    ("Uptime", "1d 0h 0m"),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn bthome_v2() {
+        // Temperature and humidity example from the BTHome format documentation, with a
+        // battery level and a negative dew point
+        let data = [
+            0x40, 0x02, 0xCA, 0x09, 0x03, 0xBF, 0x13, 0x01, 0x61, 0x08, 0x38, 0xFF,
+        ];
+
+        assert_eq!(
+            decode(0xFCD2, &data),
+            decoded(
+                "BTHome v2",
+                &[
+                    ("Temperature", "25.06 °C"),
+                    ("Humidity", "50.55 %"),
+                    ("Battery", "97 %"),
+                    ("Dew point", "-2.00 °C"),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn bthome_stops_at_unknown_objects() {
+        let data = [0x44, 0x00, 0x0A, 0xFE, 0x01, 0x02];
+
+        assert_eq!(
+            decode(0xFCD2, &data),
+            decoded(
+                "BTHome v2",
+                &[
+                    ("Trigger based", "Yes"),
+                    ("Packet ID", "10"),
+                    ("Undecoded", "fe0102"),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn bthome_encrypted() {
+        assert_eq!(
+            decode(0xFCD2, &[0x41, 0xA4, 0x72, 0x66]),
+            decoded("BTHome v2", &[("Payload", "Encrypted, a47266")])
+        );
+    }
+
+    #[test]
+    fn fast_pair() {
+        assert_eq!(
+            decode(0xFE2C, &[0x92, 0xBB, 0xBD]),
+            decoded(
+                "Google Fast Pair",
+                &[("Mode", "Discoverable"), ("Model ID", "0x92BBBD")]
+            )
+        );
+    }
+
+    #[test]
+    fn unknown_service() {
+        assert!(decode(0x180F, &[0x64]).is_empty());
+        assert!(decode_service_data(&Uuid::nil(), &[0x10, 0xEB, 0x03]).is_empty());
+    }
+
+    #[test]
+    fn truncated() {
+        for service in [0x181A, 0xFCD2, 0xFD6F, 0xFE2C, 0xFE95, 0xFEAA] {
+            assert!(decode(service, &[]).is_empty());
+        }
+
+        assert!(decode(0xFEAA, &[0x10, 0xEB]).is_empty());
+        assert!(decode(0xFEAA, &[0x00, 0xE7, 0xED, 0xD1]).is_empty());
+        assert!(decode(0xFEAA, &[0x20, 0x00, 0x0B, 0xB8, 0x18]).is_empty());
+        assert!(decode(0xFEAA, &[0x30, 0xE7, 0x01, 0x02]).is_empty());
+        assert!(decode(0x181A, &[0x00; 12]).is_empty());
+        assert!(decode(0xFD6F, &[0x00; 19]).is_empty());
+        assert!(decode(0xFE95, &[0x50, 0x20, 0xAA]).is_empty());
+
+        // An object cut short ends the list rather than reading past the end
+        assert_eq!(
+            decode(0xFCD2, &[0x40, 0x01, 0x61, 0x02, 0xCA]),
+            decoded("BTHome v2", &[("Battery", "97 %")])
+        );
+    }
+}
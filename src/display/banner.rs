@@ -1,26 +1,28 @@
 use ratatui::{
     layout::Rect,
-    style::{Style, Stylize},
-    widgets::{Block, BorderType, Borders, Paragraph},
+    style::Style,
+    widgets::{Block, Borders, Paragraph},
     Frame,
 };
 
 use crate::app::BannerType;
 
-use super::{colors::BMColors, UIState};
+use super::{colors::colors, icons::icons, UIState};
 
 pub fn draw_banner(f: &mut Frame, ui_state: &mut UIState) {
     if let Some(banner) = &mut ui_state.banner {
         let banner_icon = match banner.1 {
-            BannerType::Success => " 󰂱 ",
-            BannerType::Failure => "  ",
-            BannerType::Status => "  ",
+            BannerType::Success => icons().success,
+            BannerType::Failure => icons().failure,
+            BannerType::Status => icons().status,
         };
 
         let banner_style = match banner.1 {
-            BannerType::Success => Style::new().fg(BMColors::GREEN),
-            BannerType::Failure => Style::new().white().bg(BMColors::RED),
-            BannerType::Status => Style::new().white().bg(BMColors::DARK_GRAY),
+            BannerType::Success => Style::new().fg(colors().green),
+            BannerType::Failure => colors().fill(Style::new().fg(colors().on_color), colors().red),
+            BannerType::Status => {
+                colors().fill(Style::new().fg(colors().on_color), colors().dark_gray)
+            }
         };
 
         let p = Paragraph::new(format!("{}{}", banner_icon, banner.0.clone()))
@@ -28,8 +30,8 @@ pub fn draw_banner(f: &mut Frame, ui_state: &mut UIState) {
             .block(
                 Block::new()
                     .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
-                    .border_style(Style::new().white()),
+                    .border_set(icons().border)
+                    .border_style(Style::new().fg(colors().text)),
             );
 
         let area = f.area();
@@ -0,0 +1,115 @@
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Style, Stylize},
+    text::Span,
+    widgets::{Block, Borders, Padding, Row, Table},
+    Frame,
+};
+
+use crate::{
+    app::{Beacon, BeaconId},
+    decode::hex_string,
+};
+
+use super::{colors::colors, icons::icons, UIState};
+
+pub fn draw_beacon_table(f: &mut Frame, area: Rect, ui_state: &mut UIState) {
+    let tracker = ui_state.beacons.as_ref().borrow();
+    let beacons = tracker.beacons();
+
+    let table_header_style = Style::new().bold().fg(colors().gray);
+
+    let mut rows = vec![Row::new(
+        [
+            "Type",
+            "Identifier",
+            "TX @ 1m",
+            "RSSI",
+            "Distance",
+            "Last seen",
+            "Address",
+        ]
+        .iter()
+        .map(|t| Span::styled(*t, table_header_style)),
+    )];
+
+    let block = Block::new()
+        .title(format!(" Beacons ({}) ", beacons.len()))
+        .title_style(Style::new().fg(colors().gray).bold())
+        .padding(Padding {
+            left: 1,
+            right: 1,
+            top: 0,
+            bottom: 0,
+        })
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(colors().dark_gray))
+        .border_set(icons().border);
+
+    // Stop scrolling once the last beacon is at the bottom, under the header
+    let height = block.inner(area).height.saturating_sub(1) as usize;
+    let scroll = ui_state
+        .beacon_scroll
+        .min(beacons.len().saturating_sub(height));
+    ui_state.beacon_scroll = scroll;
+
+    rows.extend(beacons.iter().skip(scroll).map(|b| {
+        let (kind, identifier) = format_beacon_id(&b.id);
+
+        Row::new(vec![
+            Span::raw(kind),
+            Span::raw(identifier),
+            Span::raw(format!("{} dBm", b.tx_power)),
+            Span::raw(
+                b.rssi
+                    .value()
+                    .map(|r| format!("{:.0} dBm", r))
+                    .unwrap_or_default(),
+            ),
+            format_distance_span(b),
+            Span::raw(format!("{}s ago", b.last_seen.elapsed().as_secs())).fg(colors().gray),
+            Span::raw(b.address.clone()).fg(colors().gray),
+        ])
+    }));
+
+    let table = Table::new(
+        rows,
+        vec![
+            Constraint::Length(10),
+            Constraint::Percentage(99),
+            Constraint::Length(9),
+            Constraint::Length(9),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(18),
+        ],
+    )
+    .column_spacing(1)
+    .block(block);
+
+    f.render_widget(table, area);
+}
+
+fn format_beacon_id(id: &BeaconId) -> (&'static str, String) {
+    match id {
+        BeaconId::IBeacon { uuid, major, minor } => {
+            ("iBeacon", format!("{} {}/{}", uuid, major, minor))
+        }
+        BeaconId::Eddystone {
+            namespace,
+            instance,
+        } => (
+            "Eddystone",
+            format!("{} {}", hex_string(namespace), hex_string(instance)),
+        ),
+    }
+}
+
+fn format_distance_span(beacon: &Beacon) -> Span<'static> {
+    match beacon.distance() {
+        Some(d) if d < 1.0 => Span::styled(format!("{:.2} m", d), Style::new().fg(colors().green)),
+        Some(d) if d < 5.0 => Span::styled(format!("{:.1} m", d), Style::new().fg(colors().yellow)),
+        Some(d) => Span::styled(format!("{:.0} m", d), Style::new().fg(colors().orange)),
+        None => Span::raw(""),
+    }
+}
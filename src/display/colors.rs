@@ -1,14 +1,323 @@
-use ratatui::style::Color;
+//! Colour themes, degraded to what the terminal can show.
 
-pub struct BMColors;
+use std::{env, sync::OnceLock};
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+/// How many colours the terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorDepth {
+    /// Guess from `NO_COLOR`, `COLORTERM` and `TERM`.
+    #[default]
+    Auto,
+    Truecolor,
+    Ansi256,
+    Ansi16,
+    /// No colours at all, only modifiers like bold and reverse.
+    None,
+}
+
+impl ColorDepth {
+    fn detect() -> ColorDepth {
+        ColorDepth::from_vars(|name| env::var(name).unwrap_or_default())
+    }
+
+    /// Guess from environment variables looked up with `var`.
+    fn from_vars(var: impl Fn(&str) -> String) -> ColorDepth {
+        if !var("NO_COLOR").is_empty() || var("TERM") == "dumb" {
+            ColorDepth::None
+        } else if matches!(var("COLORTERM").as_str(), "truecolor" | "24bit") {
+            ColorDepth::Truecolor
+        } else if var("TERM").contains("256color") {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+}
+
+/// The palette every part of the ui draws with.
+#[derive(Debug, Clone, Copy)]
+pub struct BMColors {
+    pub blue: Color,
+    pub blue2: Color,
+    pub red: Color,
+    pub orange: Color,
+    pub yellow: Color,
+    pub green: Color,
+    pub gray: Color,
+    pub dark_gray: Color,
+    /// Text on the terminal's own background.
+    pub text: Color,
+    /// Text on a coloured background, like the header and banners.
+    pub on_color: Color,
+}
+
+static COLORS: OnceLock<BMColors> = OnceLock::new();
+
+/// Choose the palette for the rest of the session.
+pub fn init_colors(theme: Theme, depth: ColorDepth) {
+    let depth = match depth {
+        ColorDepth::Auto => ColorDepth::detect(),
+        depth => depth,
+    };
+
+    let _ = COLORS.set(theme.palette(depth));
+}
+
+pub fn colors() -> &'static BMColors {
+    COLORS.get_or_init(|| Theme::default().palette(ColorDepth::Truecolor))
+}
 
 impl BMColors {
-    pub const BLUE: Color = Color::Rgb(42, 130, 212);
-    pub const BLUE2: Color = Color::Rgb(131, 183, 230);
-    pub const RED: Color = Color::Rgb(245, 20, 30);
-    pub const ORANGE: Color = Color::Rgb(245, 145, 30);
-    pub const YELLOW: Color = Color::Rgb(245, 245, 30);
-    pub const GREEN: Color = Color::Rgb(0, 245, 10);
-    pub const GRAY: Color = Color::Rgb(150, 150, 150);
-    pub const DARK_GRAY: Color = Color::Rgb(80, 80, 80);
+    /// `style` on a `bg` background, or reversed when colours are off.
+    pub fn fill(&self, style: Style, bg: Color) -> Style {
+        match bg {
+            Color::Reset => style.add_modifier(Modifier::REVERSED),
+            bg => style.bg(bg),
+        }
+    }
+
+    /// `style` in `color`, or with `fallback` when colours are off, so what the colour
+    /// means still stands out.
+    pub fn highlight(&self, style: Style, color: Color, fallback: Modifier) -> Style {
+        match color {
+            Color::Reset => style.add_modifier(fallback),
+            color => style.fg(color),
+        }
+    }
+
+    fn map(self, f: impl Fn(Color) -> Color) -> BMColors {
+        BMColors {
+            blue: f(self.blue),
+            blue2: f(self.blue2),
+            red: f(self.red),
+            orange: f(self.orange),
+            yellow: f(self.yellow),
+            green: f(self.green),
+            gray: f(self.gray),
+            dark_gray: f(self.dark_gray),
+            text: f(self.text),
+            on_color: f(self.on_color),
+        }
+    }
+}
+
+impl Theme {
+    fn palette(self, depth: ColorDepth) -> BMColors {
+        match depth {
+            ColorDepth::Auto | ColorDepth::Truecolor => self.rgb(),
+            ColorDepth::Ansi256 => self.rgb().map(to_indexed),
+            ColorDepth::Ansi16 => self.ansi(),
+            ColorDepth::None => self.rgb().map(|_| Color::Reset),
+        }
+    }
+
+    fn rgb(self) -> BMColors {
+        match self {
+            Theme::Dark => BMColors {
+                blue: Color::Rgb(42, 130, 212),
+                blue2: Color::Rgb(131, 183, 230),
+                red: Color::Rgb(245, 20, 30),
+                orange: Color::Rgb(245, 145, 30),
+                yellow: Color::Rgb(245, 245, 30),
+                green: Color::Rgb(0, 245, 10),
+                gray: Color::Rgb(150, 150, 150),
+                dark_gray: Color::Rgb(80, 80, 80),
+                text: Color::White,
+                on_color: Color::White,
+            },
+            Theme::Light => BMColors {
+                blue: Color::Rgb(20, 95, 175),
+                blue2: Color::Rgb(60, 120, 190),
+                red: Color::Rgb(200, 10, 20),
+                orange: Color::Rgb(200, 100, 0),
+                yellow: Color::Rgb(160, 130, 0),
+                green: Color::Rgb(0, 135, 20),
+                gray: Color::Rgb(95, 95, 95),
+                dark_gray: Color::Rgb(165, 165, 165),
+                text: Color::Black,
+                on_color: Color::White,
+            },
+            Theme::HighContrast => BMColors {
+                blue: Color::Rgb(60, 150, 255),
+                blue2: Color::Rgb(170, 215, 255),
+                red: Color::Rgb(255, 60, 60),
+                orange: Color::Rgb(255, 170, 0),
+                yellow: Color::Rgb(255, 255, 0),
+                green: Color::Rgb(0, 255, 0),
+                gray: Color::Rgb(230, 230, 230),
+                dark_gray: Color::Rgb(170, 170, 170),
+                text: Color::White,
+                on_color: Color::Black,
+            },
+        }
+    }
+
+    /// The nearest of the 16 standard colours, which the terminal's own palette decides.
+    fn ansi(self) -> BMColors {
+        match self {
+            Theme::Dark => BMColors {
+                blue: Color::Blue,
+                blue2: Color::LightBlue,
+                red: Color::Red,
+                orange: Color::Yellow,
+                yellow: Color::LightYellow,
+                green: Color::LightGreen,
+                gray: Color::Gray,
+                dark_gray: Color::DarkGray,
+                text: Color::White,
+                on_color: Color::White,
+            },
+            Theme::Light => BMColors {
+                blue: Color::Blue,
+                blue2: Color::Blue,
+                red: Color::Red,
+                orange: Color::Magenta,
+                yellow: Color::Yellow,
+                green: Color::Green,
+                gray: Color::DarkGray,
+                dark_gray: Color::Gray,
+                text: Color::Black,
+                on_color: Color::White,
+            },
+            Theme::HighContrast => BMColors {
+                blue: Color::LightBlue,
+                blue2: Color::LightCyan,
+                red: Color::LightRed,
+                orange: Color::LightYellow,
+                yellow: Color::LightYellow,
+                green: Color::LightGreen,
+                gray: Color::White,
+                dark_gray: Color::Gray,
+                text: Color::White,
+                on_color: Color::Black,
+            },
+        }
+    }
+}
+
+/// The nearest colour of the 256 colour palette's 6x6x6 cube or grey ramp.
+fn to_indexed(color: Color) -> Color {
+    const LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+
+    let nearest = |c: u8| {
+        (0..LEVELS.len())
+            .min_by_key(|&i| LEVELS[i].abs_diff(c))
+            .unwrap() as u8
+    };
+    let distance = |(r2, g2, b2): (u8, u8, u8)| {
+        [(r, r2), (g, g2), (b, b2)]
+            .iter()
+            .map(|&(a, b)| (a.abs_diff(b) as u32).pow(2))
+            .sum::<u32>()
+    };
+
+    let (ri, gi, bi) = (nearest(r), nearest(g), nearest(b));
+    let cube = (
+        LEVELS[ri as usize],
+        LEVELS[gi as usize],
+        LEVELS[bi as usize],
+    );
+
+    let grey_index = ((r as u32 + g as u32 + b as u32) / 3).saturating_sub(3) / 10;
+    let grey_index = grey_index.min(23) as u8;
+    let grey = 8 + 10 * grey_index;
+
+    if distance((grey, grey, grey)) < distance(cube) {
+        Color::Indexed(232 + grey_index)
+    } else {
+        Color::Indexed(16 + 36 * ri + 6 * gi + bi)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn detect(vars: &[(&str, &str)]) -> ColorDepth {
+        let vars = vars.iter().copied().collect::<HashMap<&str, &str>>();
+        ColorDepth::from_vars(|name| vars.get(name).unwrap_or(&"").to_string())
+    }
+
+    #[test]
+    fn detect_depth() {
+        assert_eq!(detect(&[]), ColorDepth::Ansi16);
+        assert_eq!(detect(&[("TERM", "xterm")]), ColorDepth::Ansi16);
+        assert_eq!(detect(&[("TERM", "xterm-256color")]), ColorDepth::Ansi256);
+        assert_eq!(
+            detect(&[("TERM", "xterm-256color"), ("COLORTERM", "truecolor")]),
+            ColorDepth::Truecolor
+        );
+        assert_eq!(detect(&[("COLORTERM", "24bit")]), ColorDepth::Truecolor);
+
+        assert_eq!(
+            detect(&[("NO_COLOR", "1"), ("COLORTERM", "truecolor")]),
+            ColorDepth::None
+        );
+        assert_eq!(detect(&[("TERM", "dumb")]), ColorDepth::None);
+        // An empty NO_COLOR doesn't count
+        assert_eq!(
+            detect(&[("NO_COLOR", ""), ("TERM", "xterm-256color")]),
+            ColorDepth::Ansi256
+        );
+    }
+
+    #[test]
+    fn indexed() {
+        // Corners of the colour cube
+        assert_eq!(to_indexed(Color::Rgb(0, 0, 0)), Color::Indexed(16));
+        assert_eq!(to_indexed(Color::Rgb(255, 255, 255)), Color::Indexed(231));
+        assert_eq!(to_indexed(Color::Rgb(255, 0, 0)), Color::Indexed(196));
+        assert_eq!(to_indexed(Color::Rgb(0, 255, 0)), Color::Indexed(46));
+        assert_eq!(to_indexed(Color::Rgb(0, 0, 255)), Color::Indexed(21));
+
+        // Greys between the cube's levels go to the grey ramp
+        assert_eq!(to_indexed(Color::Rgb(150, 150, 150)), Color::Indexed(246));
+        assert_eq!(to_indexed(Color::Rgb(80, 80, 80)), Color::Indexed(239));
+
+        assert_eq!(to_indexed(Color::Rgb(42, 130, 212)), Color::Indexed(32));
+
+        // Anything not RGB is left alone
+        assert_eq!(to_indexed(Color::Blue), Color::Blue);
+        assert_eq!(to_indexed(Color::Reset), Color::Reset);
+    }
+
+    #[test]
+    fn no_color_falls_back_to_modifiers() {
+        let plain = Theme::Dark.palette(ColorDepth::None);
+
+        assert_eq!(plain.red, Color::Reset);
+        assert_eq!(
+            plain.highlight(Style::new(), plain.red, Modifier::BOLD),
+            Style::new().add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(
+            plain.fill(Style::new(), plain.blue),
+            Style::new().add_modifier(Modifier::REVERSED)
+        );
+
+        let colored = Theme::Dark.palette(ColorDepth::Ansi16);
+        assert_eq!(
+            colored.highlight(Style::new(), colored.red, Modifier::BOLD),
+            Style::new().fg(Color::Red)
+        );
+    }
 }
@@ -0,0 +1,73 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+
+use super::{colors::colors, icons::icons, UIState};
+
+/// Most suggestions shown above the command line at once.
+const MAX_SUGGESTIONS: usize = 8;
+
+/// Draw the command line over the controls bar, with suggestions above it.
+pub fn draw_command_line(f: &mut Frame, area: Rect, ui_state: &UIState) {
+    let Some(line) = &ui_state.command else {
+        return;
+    };
+
+    let block = Block::new()
+        .title(" Command ")
+        .title_style(Style::new().fg(colors().gray).bold())
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(colors().blue))
+        .border_set(icons().border);
+
+    let inner = block.inner(area);
+    let prompt = Line::from(vec![
+        Span::styled(":", Style::new().bold().fg(colors().blue2)),
+        Span::styled(line.input.clone(), Style::new().fg(colors().text)),
+    ]);
+
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    f.set_cursor_position((inner.x + prompt.width() as u16, inner.y));
+    f.render_widget(prompt, inner);
+
+    let devices = ui_state.listed_devices();
+    let suggestions = line.suggestions(&devices);
+
+    if suggestions.is_empty() {
+        return;
+    }
+
+    let height = suggestions.len().min(MAX_SUGGESTIONS) as u16 + 2;
+    let width = suggestions.iter().map(|s| s.len()).max().unwrap_or(0) as u16 + 4;
+
+    let rect = Rect {
+        x: inner.x,
+        y: area.y.saturating_sub(height),
+        width: width.max(24).min(area.width.saturating_sub(2)),
+        height,
+    };
+
+    let list = List::new(
+        suggestions
+            .iter()
+            .map(|s| ListItem::new(s.to_string()).fg(colors().text)),
+    )
+    .block(
+        Block::new()
+            .borders(Borders::ALL)
+            .border_style(Style::new().fg(colors().dark_gray))
+            .border_set(icons().border),
+    )
+    .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+    let mut state =
+        ListState::default().with_selected(Some(line.selected.min(suggestions.len() - 1)));
+
+    f.render_widget(Clear, rect);
+    f.render_stateful_widget(list, rect, &mut state);
+}
@@ -2,54 +2,150 @@ use ratatui::{
     layout::Rect,
     style::{Style, Stylize},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders},
+    widgets::{Block, Borders},
     Frame,
 };
 
-use super::{colors::BMColors, UIState};
+use crate::app::{Action, BTDevice, Keymap};
+
+use super::{colors::colors, icons::icons, UIState};
+
+/// Controls shown for each mode, as the actions to show keys for and a label.
+type Controls = [(&'static [Action], &'static str)];
+
+static CONTROLS: [(&[Action], &str); 11] = [
+    (&[Action::Down, Action::Up], "Select"),
+    (&[Action::Connect], "Connect"),
+    (&[Action::Pair], "Pair"),
+    (&[Action::Disconnect], "Disconnect"),
+    (&[Action::Remove], "Remove"),
+    (&[Action::ToggleUnnamed], "Show/Hide Unnamed"),
+    (&[Action::Inspect], "Inspect device"),
+    (&[Action::Details], "Details"),
+    (&[Action::Find], "Find device"),
+    (&[Action::Beacons], "Beacons"),
+    (&[Action::Log], "Event log"),
+];
+
+static INSPECT_CONTROLS: [(&[Action], &str); 4] = [
+    (&[Action::Down, Action::Up], "Select"),
+    (&[Action::PageDown, Action::PageUp], "Page"),
+    (&[Action::Copy], "Copy"),
+    (&[Action::Back], "Return"),
+];
+
+static FIND_CONTROLS: [(&[Action], &str); 2] = [
+    (&[Action::ToggleBell], "Bell on/off"),
+    (&[Action::Back], "Return"),
+];
+
+static LOG_CONTROLS: [(&[Action], &str); 6] = [
+    (&[Action::Down, Action::Up], "Scroll"),
+    (&[Action::LogDevice], "Device"),
+    (&[Action::LogSeverity], "Severity"),
+    (&[Action::SaveLog], "Save"),
+    (&[Action::LogPath], "Log file"),
+    (&[Action::Back], "Return"),
+];
 
-static CONTROLS: [(&'static str, &'static str); 7] = [
-    ("jk", "Select"),
-    ("c", "Connect"),
-    ("p", "Pair"),
-    ("d", "Disconnect"),
-    ("r", "Remove"),
-    ("h", "Show/Hide Unammed"),
-    ("i", "Inspect device"),
+static BEACON_CONTROLS: [(&[Action], &str); 2] = [
+    (&[Action::Down, Action::Up], "Scroll"),
+    (&[Action::Back], "Return"),
 ];
 
-static INSPECT_CONTROLS: [(&'static str, &'static str); 1] = [("ESC", "Return")];
+pub fn draw_browse_controls(f: &mut Frame, area: Rect, ui_state: &mut UIState) {
+    draw_controls(f, area, ui_state, &CONTROLS);
+}
+
+pub fn draw_inspect_controls(f: &mut Frame, area: Rect, ui_state: &mut UIState) {
+    draw_controls(f, area, ui_state, &INSPECT_CONTROLS);
+}
+
+pub fn draw_beacon_controls(f: &mut Frame, area: Rect, ui_state: &mut UIState) {
+    draw_controls(f, area, ui_state, &BEACON_CONTROLS);
+}
+
+pub fn draw_find_controls(f: &mut Frame, area: Rect, ui_state: &mut UIState) {
+    draw_controls(f, area, ui_state, &FIND_CONTROLS);
+}
+
+pub fn draw_log_controls(f: &mut Frame, area: Rect, ui_state: &mut UIState) {
+    draw_controls(f, area, ui_state, &LOG_CONTROLS);
+}
 
-pub fn draw_browse_controls(f: &mut Frame, area: Rect) {
-    draw_controls(f, area, &CONTROLS);
+/// Whether `action` can do anything to the selected device.
+fn applies_to(action: Action, device: Option<&BTDevice>) -> bool {
+    match action {
+        Action::Connect => device.is_some_and(|d| !d.connected),
+        Action::Pair => device.is_some_and(|d| !d.paired),
+        Action::Disconnect => device.is_some_and(|d| d.connected),
+        Action::Remove => device.is_some_and(|d| d.paired),
+        Action::Inspect | Action::Find => device.is_some(),
+        _ => true,
+    }
 }
 
-pub fn draw_inspect_controls(f: &mut Frame, area: Rect) {
-    draw_controls(f, area, &INSPECT_CONTROLS);
+/// Keys bound to `actions`, run together when they're all single characters like `jk`.
+fn format_keys(keymap: &Keymap, actions: &[Action]) -> Option<String> {
+    let keys = actions
+        .iter()
+        .filter_map(|a| keymap.key_for(*a).map(|k| k.to_string()))
+        .collect::<Vec<String>>();
+
+    if keys.is_empty() {
+        None
+    } else if keys.iter().all(|k| k.chars().count() == 1) {
+        Some(keys.concat())
+    } else {
+        Some(keys.join("/"))
+    }
 }
 
-pub fn draw_controls(f: &mut Frame, area: Rect, control_strings: &[(&'static str, &'static str)]) {
+pub fn draw_controls(f: &mut Frame, area: Rect, ui_state: &mut UIState, controls: &Controls) {
+    let keymap = &ui_state.keymap;
+    let device = ui_state.selected_device();
+
     let block = Block::new()
         .borders(Borders::ALL)
-        .border_style(Style::new().fg(BMColors::DARK_GRAY))
-        .border_type(BorderType::Rounded);
+        .border_style(Style::new().fg(colors().dark_gray))
+        .border_set(icons().border);
 
     let block_inner = block.inner(area);
 
-    let mut width_remaining = block_inner.width as i32 - 4;
+    // Always leave room to point at the help overlay, which lists whatever gets cut off
+    let help_spans = match format_keys(keymap, &[Action::Help]) {
+        Some(k) => format_control(format!(" [{}] ", k), "Help ".to_string(), true),
+        None => vec![],
+    };
+    let help_width = help_spans.iter().map(Span::width).sum::<usize>();
+
+    let mut width_remaining = block_inner.width as i32 - 4 - help_width as i32;
 
     let mut control_spans: Vec<Span> = vec![];
+    // Where each control starts and ends in the line, and the action a click runs
+    let mut hits: Vec<(usize, usize, Action)> = vec![];
 
-    for &(a, b) in control_strings.iter() {
-        let t0 = format!(" [{}] ", a);
-        let t1 = format!("{} ", b);
-        let l = t0.len() + t1.len();
+    for (actions, a, b) in controls
+        .iter()
+        .filter_map(|(actions, label)| format_keys(keymap, actions).map(|k| (actions, k, label)))
+    {
+        let enabled = actions.iter().all(|a| applies_to(*a, device.as_ref()));
+        let spans = format_control(format!(" [{}] ", a), format!("{} ", b), enabled);
+        let l = spans.iter().map(Span::width).sum::<usize>();
 
         width_remaining -= l as i32;
 
         if width_remaining > 0 {
-            control_spans.push(Span::styled(t0, Style::new().bold().fg(BMColors::BLUE2)));
-            control_spans.push(Span::styled(t1, Style::new().fg(BMColors::GRAY)));
+            let start = control_spans.iter().map(Span::width).sum::<usize>();
+
+            // Greyed out controls do nothing, so clicking them shouldn't either. Nor should
+            // a pair like [jk] Select, where no one of its actions is what a click means.
+            if let [action] = actions {
+                if enabled {
+                    hits.push((start, start + l, *action));
+                }
+            }
+            control_spans.extend(spans);
         } else {
             break;
         }
@@ -58,10 +154,17 @@ pub fn draw_controls(f: &mut Frame, area: Rect, control_strings: &[(&'static str
     if width_remaining < 0 {
         control_spans.push(Span::styled(
             "...".to_string(),
-            Style::new().fg(BMColors::GRAY),
+            Style::new().fg(colors().gray),
         ));
     }
 
+    if help_width > 0 {
+        let start = control_spans.iter().map(Span::width).sum::<usize>();
+        hits.push((start, start + help_width, Action::Help));
+    }
+
+    control_spans.extend(help_spans);
+
     let controls = Line::from(control_spans);
 
     let offset = block_inner.width as i32 / 2 - controls.width() as i32 / 2;
@@ -75,17 +178,58 @@ pub fn draw_controls(f: &mut Frame, area: Rect, control_strings: &[(&'static str
 
     f.render_widget(block, area);
     f.render_widget(controls, controls_rect);
+
+    ui_state.hit_areas.controls = hits
+        .into_iter()
+        .map(|(start, end, action)| {
+            let rect = Rect {
+                x: controls_rect.x + start as u16,
+                width: (end - start) as u16,
+                ..controls_rect
+            };
+            (rect, action)
+        })
+        .collect();
 }
 
-pub fn draw_quit_hint(f: &mut Frame, area: Rect, _: &mut UIState) {
+/// A control's keys and label, greyed out when it doesn't apply.
+fn format_control(keys: String, label: String, enabled: bool) -> Vec<Span<'static>> {
+    if enabled {
+        vec![
+            Span::styled(keys, Style::new().bold().fg(colors().blue2)),
+            Span::styled(label, Style::new().fg(colors().gray)),
+        ]
+    } else {
+        let disabled = Style::new().fg(colors().dark_gray).dim();
+        vec![
+            Span::styled(keys, disabled.bold()),
+            Span::styled(label, disabled),
+        ]
+    }
+}
+
+pub fn draw_quit_hint(f: &mut Frame, area: Rect, ui_state: &mut UIState) {
+    let Some(quit_key) = ui_state.keymap.key_for(Action::Quit) else {
+        return;
+    };
+
     let quit_hint = Line::from(vec![
-        Span::styled("", Style::new().fg(BMColors::DARK_GRAY)),
-        Span::styled(" q ", Style::new().bold().white().bg(BMColors::DARK_GRAY)),
-        Span::styled("quit ", Style::new().white().bg(BMColors::DARK_GRAY)),
-        Span::styled("", Style::new().fg(BMColors::DARK_GRAY)),
+        Span::styled(icons().left_cap, Style::new().fg(colors().dark_gray)),
+        Span::styled(
+            format!(" {} ", quit_key),
+            colors().fill(
+                Style::new().bold().fg(colors().on_color),
+                colors().dark_gray,
+            ),
+        ),
+        Span::styled(
+            "quit ",
+            colors().fill(Style::new().fg(colors().on_color), colors().dark_gray),
+        ),
+        Span::styled(icons().right_cap, Style::new().fg(colors().dark_gray)),
     ]);
 
-    let x = area.width as u16 - 1 - quit_hint.width() as u16;
+    let x = area.width - 1 - quit_hint.width() as u16;
 
     let rect = Rect {
         x,
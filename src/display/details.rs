@@ -0,0 +1,82 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Borders, ListState, Padding, Paragraph},
+    Frame,
+};
+use serde::Deserialize;
+
+use super::{colors::colors, icons::icons, inspect::draw_device_details, UIState};
+
+/// Where details of the highlighted device are shown next to the device list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DetailsPane {
+    #[default]
+    Hidden,
+    Right,
+    Bottom,
+}
+
+impl DetailsPane {
+    pub fn next(&self) -> Self {
+        match self {
+            DetailsPane::Hidden => DetailsPane::Right,
+            DetailsPane::Right => DetailsPane::Bottom,
+            DetailsPane::Bottom => DetailsPane::Hidden,
+        }
+    }
+
+    /// Split `area` between the device list and the pane, if it's shown.
+    pub fn split(&self, area: Rect) -> (Rect, Option<Rect>) {
+        let direction = match self {
+            DetailsPane::Hidden => return (area, None),
+            DetailsPane::Right => Direction::Horizontal,
+            DetailsPane::Bottom => Direction::Vertical,
+        };
+
+        let layout = Layout::default()
+            .direction(direction)
+            .constraints(vec![Constraint::Percentage(55), Constraint::Percentage(45)])
+            .split(area);
+
+        (layout[0], Some(layout[1]))
+    }
+}
+
+pub fn draw_details_pane(f: &mut Frame, area: Rect, ui_state: &UIState) {
+    let device = ui_state.selected_device();
+
+    match (device, &ui_state.details_fields) {
+        (Some(device), Some((address, fields))) if *address == device.address => {
+            let histories = ui_state.histories.as_ref().borrow();
+
+            draw_device_details(
+                f,
+                area,
+                histories.get(&device.address),
+                &device,
+                fields,
+                &mut ListState::default(),
+                Padding::horizontal(1),
+            );
+        }
+        _ => {
+            let block = Block::new()
+                .title(" Details ")
+                .title_style(Style::new().bold().fg(colors().gray))
+                .padding(Padding::horizontal(1))
+                .borders(Borders::ALL)
+                .border_style(Style::new().fg(colors().dark_gray))
+                .border_set(icons().border);
+
+            let hint = Line::styled(
+                "Select a device to see its details",
+                Style::new().fg(colors().gray),
+            );
+
+            f.render_widget(Paragraph::new(hint).block(block), area);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Gauge, Padding, Paragraph, Sparkline},
+    Frame,
+};
+
+use crate::app::{Finder, Trend, RSSI_CEILING, RSSI_FLOOR};
+
+use super::{colors::colors, icons::icons, UIState};
+
+pub fn draw_find_panel(f: &mut Frame, area: Rect, ui_state: &mut UIState) {
+    let Some(finder) = &ui_state.finder else {
+        return;
+    };
+
+    let block = Block::new()
+        .title(format!(" Finding {} ({}) ", finder.name, finder.address))
+        .title_style(Style::new().fg(colors().gray).bold())
+        .padding(Padding::uniform(1))
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(colors().dark_gray))
+        .border_set(icons().border);
+
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![
+            Constraint::Length(5),
+            Constraint::Length(1),
+            Constraint::Length(2),
+            Constraint::Min(3),
+        ])
+        .split(inner);
+
+    let strength = finder.strength().unwrap_or(0.0);
+
+    let gauge = Gauge::default()
+        .gauge_style(
+            Style::new()
+                .fg(strength_color(strength))
+                .bg(colors().dark_gray),
+        )
+        .ratio(strength)
+        .label(match finder.rssi.value() {
+            Some(rssi) => format!("{:.0} dBm", rssi),
+            None => "No signal yet".to_string(),
+        });
+
+    f.render_widget(gauge, layout[0]);
+    f.render_widget(format_trend_line(finder), layout[1]);
+
+    let history = finder
+        .history
+        .iter()
+        .map(|r| (r.clamp(RSSI_FLOOR, RSSI_CEILING) - RSSI_FLOOR) as u64)
+        .collect::<Vec<u64>>();
+
+    let sparkline = Sparkline::default()
+        .block(
+            Block::new()
+                .title(" Last minute ")
+                .title_style(Style::new().fg(colors().gray))
+                .borders(Borders::TOP)
+                .border_style(Style::new().fg(colors().dark_gray)),
+        )
+        .data(&history)
+        .max((RSSI_CEILING - RSSI_FLOOR) as u64)
+        .style(Style::new().fg(colors().blue2));
+
+    f.render_widget(sparkline, layout[3]);
+}
+
+fn format_trend_line(finder: &Finder) -> Paragraph<'static> {
+    let trend = match finder.trend() {
+        Some(Trend::Hotter) => Span::styled(
+            format!("{} Hotter", icons().hotter),
+            Style::new().fg(colors().red).bold(),
+        ),
+        Some(Trend::Colder) => Span::styled(
+            format!("{} Colder", icons().colder),
+            Style::new().fg(colors().blue).bold(),
+        ),
+        Some(Trend::Steady) => Span::styled(
+            format!("{} Steady", icons().steady),
+            Style::new().fg(colors().gray),
+        ),
+        None => Span::styled(
+            format!("Gathering readings{}", icons().ellipsis),
+            Style::new().fg(colors().gray),
+        ),
+    };
+
+    let bell = if finder.bell {
+        Span::styled("   Bell on", Style::new().fg(colors().gray))
+    } else {
+        Span::styled("   Bell off", Style::new().fg(colors().dark_gray))
+    };
+
+    Paragraph::new(Line::from(vec![trend, bell])).centered()
+}
+
+fn strength_color(strength: f64) -> ratatui::style::Color {
+    match strength {
+        s if s >= 0.7 => colors().green,
+        s if s >= 0.4 => colors().yellow,
+        s if s >= 0.2 => colors().orange,
+        _ => colors().red,
+    }
+}
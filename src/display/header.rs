@@ -5,16 +5,16 @@ use ratatui::{
     Frame,
 };
 
-use super::{colors::BMColors, UIState};
+use super::{colors::colors, icons::icons, UIState};
 
 pub fn draw_header(f: &mut Frame, _: Rect, _: &mut UIState) {
     let title = Line::from(vec![
-        Span::styled("", Style::new().fg(BMColors::BLUE)),
+        Span::styled(icons().left_cap, Style::new().fg(colors().blue)),
         Span::styled(
-            " BlueTool 󰂯 ",
-            Style::new().bold().white().bg(BMColors::BLUE),
+            format!(" BlueTool {}", icons().bluetooth),
+            colors().fill(Style::new().bold().fg(colors().on_color), colors().blue),
         ),
-        Span::styled("", Style::new().fg(BMColors::BLUE)),
+        Span::styled(icons().right_cap, Style::new().fg(colors().blue)),
         Span::raw(" "),
         Span::styled(
             format!("v{}", env!("CARGO_PKG_VERSION")),
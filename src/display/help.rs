@@ -0,0 +1,84 @@
+use ratatui::{
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Padding, Paragraph},
+    Frame,
+};
+
+use crate::app::{Action, AppMode, Keymap};
+
+use super::{colors::colors, icons::icons, UIState};
+
+/// Width of the column of keys in the help overlay.
+const KEY_WIDTH: usize = 14;
+
+pub fn draw_help_overlay(f: &mut Frame, ui_state: &mut UIState, mode: &AppMode) {
+    let lines = format_help_lines(&ui_state.keymap, mode);
+
+    let area = f.area();
+    let width = area.width.saturating_sub(4).min(64);
+    let height = area.height.saturating_sub(4).min(lines.len() as u16 + 2);
+
+    let rect = Rect {
+        x: (area.width - width) / 2,
+        y: (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    // Stop scrolling once the last line is at the bottom
+    let max_scroll = (lines.len() as u16).saturating_sub(height.saturating_sub(2));
+    let scroll = ui_state.help.unwrap_or(0).min(max_scroll);
+    ui_state.help = Some(scroll);
+
+    let block = Block::new()
+        .title(" Keys ")
+        .title_style(Style::new().bold().fg(colors().text))
+        .padding(Padding::horizontal(1))
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(colors().dark_gray))
+        .border_set(icons().border);
+
+    f.render_widget(Clear, rect);
+    f.render_widget(Paragraph::new(lines).block(block).scroll((scroll, 0)), rect);
+}
+
+/// Every action available in `mode` with all of its keys, under a heading for each category.
+fn format_help_lines(keymap: &Keymap, mode: &AppMode) -> Vec<Line<'static>> {
+    let mut lines = vec![];
+    let mut category = "";
+
+    for action in Action::ALL.iter().filter(|a| a.available(mode)) {
+        let keys = keymap
+            .keys_for(*action)
+            .map(|k| k.to_string())
+            .collect::<Vec<String>>();
+
+        if keys.is_empty() {
+            continue;
+        }
+
+        if action.category() != category {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+
+            category = action.category();
+            lines.push(Line::styled(
+                category,
+                Style::new().bold().fg(colors().gray),
+            ));
+        }
+
+        lines.push(Line::from(vec![
+            Span::styled(
+                format!("  {:<KEY_WIDTH$}", keys.join(", ")),
+                Style::new().bold().fg(colors().blue2),
+            ),
+            Span::styled(action.description(), Style::new().fg(colors().text)),
+        ]));
+    }
+
+    lines
+}
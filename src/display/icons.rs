@@ -1,16 +1,353 @@
+//! Glyphs for each icon set, from Nerd Font icons down to plain ASCII for terminals whose
+//! font has neither.
+
+use std::{env, sync::OnceLock};
+
+use ratatui::symbols::border;
+use serde::Deserialize;
+
+use crate::app::BTDevice;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IconSet {
+    /// ASCII on the Linux console or without a UTF-8 locale, Unicode otherwise. Nerd Font
+    /// icons need a patched font, so they're only used when asked for.
+    #[default]
+    Auto,
+    NerdFont,
+    /// Emoji and characters found in any font.
+    Unicode,
+    Ascii,
+}
+
+impl IconSet {
+    fn detect() -> IconSet {
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|v| env::var(v).ok().filter(|l| !l.is_empty()))
+            .unwrap_or_default()
+            .to_lowercase();
+
+        if env::var("TERM").is_ok_and(|t| t == "linux")
+            || !(locale.contains("utf-8") || locale.contains("utf8"))
+        {
+            IconSet::Ascii
+        } else {
+            IconSet::Unicode
+        }
+    }
+}
+
+/// The glyphs every part of the ui draws with.
+#[derive(Debug, Clone, Copy)]
+pub struct Icons {
+    pub set: IconSet,
+    pub connected: &'static str,
+    pub paired: &'static str,
+    /// Shown in the header after the app name.
+    pub bluetooth: &'static str,
+    pub success: &'static str,
+    pub failure: &'static str,
+    pub status: &'static str,
+    /// Rounded ends around the header and quit hint.
+    pub left_cap: &'static str,
+    pub right_cap: &'static str,
+    pub hotter: &'static str,
+    pub colder: &'static str,
+    pub steady: &'static str,
+    pub ellipsis: &'static str,
+    pub about: &'static str,
+    pub warning: &'static str,
+    /// Separates parts of a title.
+    pub dot: &'static str,
+    /// Sparkline bars, from lowest to highest.
+    pub bars: [char; 8],
+    pub border: border::Set,
+}
+
+static ICONS: OnceLock<Icons> = OnceLock::new();
+
+/// Choose the icon set for the rest of the session.
+pub fn init_icons(set: IconSet) {
+    let set = match set {
+        IconSet::Auto => IconSet::detect(),
+        set => set,
+    };
+
+    let _ = ICONS.set(Icons::new(set));
+}
+
+pub fn icons() -> &'static Icons {
+    ICONS.get_or_init(|| Icons::new(IconSet::Unicode))
+}
+
+impl Icons {
+    fn new(set: IconSet) -> Icons {
+        match set {
+            IconSet::Auto | IconSet::NerdFont => Icons {
+                set: IconSet::NerdFont,
+                connected: "󰂱",
+                paired: "󰂯",
+                bluetooth: "󰂯 ",
+                success: " 󰂱 ",
+                failure: "  ",
+                status: "  ",
+                left_cap: "",
+                right_cap: "",
+                ..Icons::new(IconSet::Unicode)
+            },
+            IconSet::Unicode => Icons {
+                set,
+                connected: "●",
+                paired: "○",
+                bluetooth: "",
+                success: " ✔ ",
+                failure: " ✘ ",
+                status: " ℹ ",
+                left_cap: "▐",
+                right_cap: "▌",
+                hotter: "▲",
+                colder: "▼",
+                steady: "●",
+                ellipsis: "…",
+                about: "≈",
+                warning: "⚠",
+                dot: "·",
+                bars: ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'],
+                border: border::ROUNDED,
+            },
+            IconSet::Ascii => Icons {
+                set,
+                connected: "*",
+                paired: "+",
+                bluetooth: "",
+                success: " OK ",
+                failure: " !! ",
+                status: " -- ",
+                left_cap: "",
+                right_cap: "",
+                hotter: "^",
+                colder: "v",
+                steady: "=",
+                ellipsis: "...",
+                about: "~",
+                warning: "!",
+                dot: "|",
+                bars: [' ', '.', ':', '-', '=', '+', '*', '#'],
+                border: border::Set {
+                    top_left: "+",
+                    top_right: "+",
+                    bottom_left: "+",
+                    bottom_right: "+",
+                    vertical_left: "|",
+                    vertical_right: "|",
+                    horizontal_top: "-",
+                    horizontal_bottom: "-",
+                },
+            },
+        }
+    }
+
+    /// A battery glyph for `level`, or `None` when the level should be written out instead.
+    pub fn battery(&self, level: u8) -> Option<&'static str> {
+        match self.set {
+            IconSet::Auto | IconSet::NerdFont => Some(match level {
+                0..10 => " ",
+                10..40 => " ",
+                40..70 => " ",
+                70..90 => " ",
+                _ => " ",
+            }),
+            IconSet::Unicode => Some(match level {
+                0..10 => "▱▱▱▱ ",
+                10..40 => "▰▱▱▱ ",
+                40..70 => "▰▰▱▱ ",
+                70..90 => "▰▰▰▱ ",
+                _ => "▰▰▰▰ ",
+            }),
+            IconSet::Ascii => None,
+        }
+    }
+
+    /// Signal strength for an RSSI of `rssi` dBm.
+    pub fn signal(&self, rssi: i16) -> &'static str {
+        let strength = match rssi {
+            -50..0 => 2,
+            -90..-50 => 1,
+            _ => 0,
+        };
+
+        match self.set {
+            IconSet::Auto | IconSet::NerdFont => ["󰢼 ", "󰢽 ", "󰢾 "][strength],
+            IconSet::Unicode => ["▂   ", "▂▄  ", "▂▄▆ "][strength],
+            IconSet::Ascii => ["|   ", "||  ", "||| "][strength],
+        }
+    }
+}
+
+/// Get the icon for a device, falling back on its class of device or LE appearance when
+/// BlueZ doesn't report an icon.
+pub fn get_icon_for_device(device: &BTDevice) -> &'static str {
+    if device.icon_name.is_empty() {
+        let icon_name = device
+            .class
+            .and_then(|c| c.icon_name())
+            .or_else(|| device.appearance.and_then(|a| a.icon_name()))
+            .unwrap_or("");
+
+        get_icon_for_bt_type(icon_name)
+    } else {
+        get_icon_for_bt_type(&device.icon_name)
+    }
+}
+
 pub fn get_icon_for_bt_type(bt_type: &str) -> &'static str {
+    match icons().set {
+        IconSet::Auto | IconSet::NerdFont => get_nerd_font_icon(bt_type),
+        IconSet::Unicode => get_unicode_icon(bt_type),
+        IconSet::Ascii => get_ascii_icon(bt_type),
+    }
+}
+
+fn get_nerd_font_icon(bt_type: &str) -> &'static str {
     match bt_type {
         "audio-headphones" | "headphones" => " ",
         "audio-headset" | "headset" => " ",
         "speaker" | "audio-speakers" | "car-audio" | "audio" => "󰓃 ",
         "input-keyboard" | "keyboard" => " ",
         "audio-card" => "󱀞 ",
-        "computer" | "laptop" | "tablet" => " ",
+        "computer" | "laptop" | "tablet" | "input-tablet" => " ",
         "phone" => " ",
         "input-mouse" | "mouse" => "󰍽 ",
         "input-gaming" | "gamepad" | "controller" => "󰊗 ",
         "printer" => "󰐪 ",
         "smart-light" | "lightbulb" => "󰌵 ",
+        "watch" => " ",
+        "tag" => " ",
+        "heart-rate" => " ",
+        "thermometer" | "sensor" => " ",
+        "video-display" => " ",
+        "microphone" => " ",
+        "hearing-aid" => "󰋎 ",
+        "camera-photo" => " ",
+        "camera-video" => " ",
+        "network-wireless" => "󰖩 ",
         _ => " ",
     }
 }
+
+/// Emoji are two columns wide, so unlike the other sets they need no trailing space.
+fn get_unicode_icon(bt_type: &str) -> &'static str {
+    match bt_type {
+        "audio-headphones" | "headphones" | "audio-headset" | "headset" => "🎧",
+        "speaker" | "audio-speakers" | "car-audio" | "audio" => "🔊",
+        "input-keyboard" | "keyboard" => "⌨ ",
+        "audio-card" => "🎵",
+        "computer" | "laptop" | "tablet" | "input-tablet" => "💻",
+        "phone" => "📱",
+        "input-mouse" | "mouse" => "🐁",
+        "input-gaming" | "gamepad" | "controller" => "🎮",
+        "printer" => "⎙ ",
+        "smart-light" | "lightbulb" => "💡",
+        "watch" => "⌚",
+        "tag" => "🔖",
+        "heart-rate" => "💓",
+        "thermometer" | "sensor" => "📡",
+        "video-display" => "📺",
+        "microphone" => "🎤",
+        "hearing-aid" => "🦻",
+        "camera-photo" => "📷",
+        "camera-video" => "📹",
+        "network-wireless" => "📶",
+        _ => "• ",
+    }
+}
+
+fn get_ascii_icon(bt_type: &str) -> &'static str {
+    match bt_type {
+        "audio-headphones" | "headphones" | "audio-headset" | "headset" => "hp",
+        "speaker" | "audio-speakers" | "car-audio" | "audio" => "sp",
+        "input-keyboard" | "keyboard" => "kb",
+        "audio-card" => "au",
+        "computer" | "laptop" | "tablet" | "input-tablet" => "pc",
+        "phone" => "ph",
+        "input-mouse" | "mouse" => "ms",
+        "input-gaming" | "gamepad" | "controller" => "gp",
+        "printer" => "pr",
+        "smart-light" | "lightbulb" => "lt",
+        "watch" => "wt",
+        "tag" => "tg",
+        "heart-rate" => "hr",
+        "thermometer" | "sensor" => "sn",
+        "video-display" => "tv",
+        "microphone" => "mc",
+        "hearing-aid" => "ha",
+        "camera-photo" => "cm",
+        "camera-video" => "vc",
+        "network-wireless" => "nw",
+        _ => "- ",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every icon name the class of device and appearance decoders give.
+    const DECODED_ICONS: [&str; 25] = [
+        "audio",
+        "audio-card",
+        "audio-headphones",
+        "audio-headset",
+        "audio-speakers",
+        "camera-photo",
+        "camera-video",
+        "computer",
+        "hearing-aid",
+        "heart-rate",
+        "input-gaming",
+        "input-keyboard",
+        "input-mouse",
+        "input-tablet",
+        "microphone",
+        "network-wireless",
+        "phone",
+        "printer",
+        "sensor",
+        "smart-light",
+        "tablet",
+        "tag",
+        "thermometer",
+        "video-display",
+        "watch",
+    ];
+
+    #[test]
+    fn decoded_icons_have_glyphs() {
+        let sets: [fn(&str) -> &'static str; 3] =
+            [get_nerd_font_icon, get_unicode_icon, get_ascii_icon];
+
+        for icon in sets {
+            let fallback = icon("");
+
+            for name in DECODED_ICONS {
+                assert_ne!(icon(name), fallback, "no glyph for {}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn ascii_icons_are_ascii() {
+        for name in DECODED_ICONS {
+            assert!(get_ascii_icon(name).is_ascii());
+        }
+
+        let icons = Icons::new(IconSet::Ascii);
+        for glyph in [icons.connected, icons.paired, icons.warning, icons.dot] {
+            assert!(glyph.is_ascii());
+        }
+        assert!(icons.bars.iter().all(char::is_ascii));
+        assert_eq!(icons.battery(50), None);
+    }
+}
@@ -1,46 +1,140 @@
+use std::time::Instant;
+
 use bluer::Uuid;
 use ratatui::{
-    layout::Rect,
-    style::{Style, Stylize},
-    text::Line,
-    widgets::{Block, BorderType, Borders, Padding, Paragraph, Wrap},
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style, Stylize},
+    symbols::Marker,
+    text::{Line, Span, Text},
+    widgets::{
+        Axis, Block, Borders, Chart, Dataset, GraphType, List, ListItem, ListState, Padding,
+    },
     Frame,
 };
 
-use crate::app::BTDevice;
+use crate::{
+    app::{BTDevice, DeviceHistory, Series, RSSI_CEILING, RSSI_FLOOR},
+    decode::{
+        decode_manufacturer_data, decode_service_data, format_company, format_uuid, hex_dump,
+        hex_string, AddressKind, Decoded,
+    },
+};
 
-use super::{colors::BMColors, UIState};
+use super::{colors::colors, icons::icons, table::format_drain_spans, UIState};
 
-pub async fn format_inspect_text(device: BTDevice) -> Paragraph<'static> {
-    let mut lines: Vec<Line> = vec![];
+/// A line of the inspect panel, with what to copy when it's selected.
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub text: String,
+    pub value: Option<String>,
+}
 
-    lines.push(Line::raw(format!(
-        "Address type: {}",
-        device
-            .inner
-            .address_type()
-            .await
-            .map_or_else(|_| "???".to_string(), |a| a.to_string()),
-    )));
-    lines.push(Line::raw(format!(
-        "Name: {}",
+impl Field {
+    /// A `label: value` line, copying the value.
+    fn labelled(label: &str, value: impl ToString) -> Self {
+        let value = value.to_string();
+
+        Field {
+            text: format!("{}: {}", label, value),
+            value: Some(value),
+        }
+    }
+
+    fn heading(text: &str) -> Self {
+        Field {
+            text: text.to_string(),
+            value: None,
+        }
+    }
+
+    fn indented(mut self, depth: usize) -> Self {
+        self.text = format!("{}{}", "  ".repeat(depth), self.text);
+        self
+    }
+}
+
+/// The inspect panel's fields, and which one is selected.
+#[derive(Debug, Default)]
+pub struct InspectView {
+    pub fields: Vec<Field>,
+    pub state: ListState,
+    /// Rows the panel had when it was last drawn, for scrolling a page at a time.
+    pub page: u16,
+}
+
+impl InspectView {
+    pub fn new(fields: Vec<Field>) -> Self {
+        InspectView {
+            fields,
+            state: ListState::default().with_selected(Some(0)),
+            page: 0,
+        }
+    }
+
+    pub fn selected(&self) -> Option<&Field> {
+        self.fields.get(self.state.selected()?)
+    }
+}
+
+pub async fn format_inspect_fields(device: BTDevice) -> Vec<Field> {
+    let mut fields = vec![];
+
+    fields.push(Field {
+        text: format!(
+            "Address: {} ({})",
+            device.address,
+            device
+                .inner
+                .address_type()
+                .await
+                .map_or_else(|_| "???".to_string(), |a| a.to_string()),
+        ),
+        value: Some(device.address.clone()),
+    });
+    fields.push(
+        Field::labelled(
+            match device.address_kind {
+                AddressKind::Public(_) => "Vendor",
+                _ => "Kind",
+            },
+            device.address_kind,
+        )
+        .indented(1),
+    );
+    fields.push(
+        Field::labelled(
+            "Stable identity",
+            if device.address_kind.is_stable() {
+                "Yes"
+            } else {
+                "No, address rotates"
+            },
+        )
+        .indented(1),
+    );
+    fields.push(Field::labelled(
+        "Name",
         device
             .inner
             .name()
             .await
             .unwrap_or(None)
-            .unwrap_or("???".to_string())
-    )));
-    lines.push(Line::raw(format!("Icon type: {}", device.icon_name)));
-    lines.push(Line::raw(format!(
-        "Class: {}",
-        device
-            .inner
-            .class()
-            .await
-            .unwrap_or(None)
-            .unwrap_or_default()
-    )));
+            .unwrap_or("???".to_string()),
+    ));
+    fields.push(Field::labelled("Alias", &device.alias));
+    fields.push(Field::labelled("Icon type", &device.icon_name));
+    if let Some(class) = device.class {
+        fields.push(Field::labelled("Class", class));
+
+        let services = class.services();
+        if !services.is_empty() {
+            fields.push(Field::labelled("Services", services.join(", ")).indented(1));
+        }
+    }
+
+    if let Some(appearance) = device.appearance {
+        fields.push(Field::labelled("Appearance", appearance));
+    }
 
     let mut uuids = device
         .inner
@@ -52,55 +146,86 @@ pub async fn format_inspect_text(device: BTDevice) -> Paragraph<'static> {
         .collect::<Vec<Uuid>>();
     uuids.sort();
 
-    lines.push(Line::raw(format!(
-        "UUIDS: {}",
-        uuids
-            .iter()
-            .map(|u| u.to_string())
-            .collect::<Vec<String>>()
-            .join(", ")
-    )));
-
-    lines.push(Line::raw(format!("Paired: {}", device.paired)));
-    lines.push(Line::raw(format!("Connected: {}", device.connected)));
-    lines.push(Line::raw(format!(
-        "RSSI: {}",
+    fields.push(Field::heading("UUIDs:"));
+    fields.extend(uuids.iter().map(|uuid| Field {
+        text: format!("  {}", format_uuid(uuid)),
+        value: Some(uuid.to_string()),
+    }));
+
+    fields.push(Field::labelled("Paired", device.paired));
+    fields.push(Field::labelled("Connected", device.connected));
+    fields.push(Field::labelled(
+        "RSSI",
         device
             .inner
             .rssi()
             .await
             .unwrap_or(None)
-            .map_or_else(|| "???".to_string(), |r| r.to_string())
-    )));
-    lines.push(Line::raw(format!(
-        "TX Power: {}",
-        device
-            .inner
-            .tx_power()
-            .await
-            .unwrap_or(None)
-            .map_or_else(|| "???".to_string(), |t| t.to_string())
-    )));
-    lines.push(Line::raw(format!(
-        "Manufacturer Data: {:?}",
+            .map_or_else(|| "???".to_string(), |r| r.to_string()),
+    ));
+    fields.push(Field::labelled(
+        "TX Power",
         device
-            .inner
-            .manufacturer_data()
-            .await
-            .unwrap_or(None)
-            .unwrap_or_default()
-    )));
-    lines.push(Line::raw(format!(
-        "Service data: {:?}",
-        device
-            .inner
-            .service_data()
-            .await
-            .unwrap_or(None)
-            .unwrap_or_default()
-    )));
+            .tx_power
+            .map_or_else(|| "???".to_string(), |t| t.to_string()),
+    ));
+
+    let mut manufacturer_data = device.manufacturer_data.iter().collect::<Vec<_>>();
+    manufacturer_data.sort();
+
+    fields.push(Field::heading("Manufacturer Data:"));
+    for (&company, data) in manufacturer_data {
+        fields.extend(format_data(
+            format_company(company),
+            data,
+            &decode_manufacturer_data(company, data),
+        ));
+    }
+
+    let mut service_data = device.service_data.iter().collect::<Vec<_>>();
+    service_data.sort();
 
-    Paragraph::new(lines)
+    fields.push(Field::heading("Service data:"));
+    for (uuid, data) in service_data {
+        fields.extend(format_data(
+            format_uuid(uuid),
+            data,
+            &decode_service_data(uuid, data),
+        ));
+    }
+
+    fields
+}
+
+/// Advertised data under its `source`, decoded if we know how. Each line copies the raw bytes
+/// as hex, besides decoded fields which copy their own value.
+fn format_data(source: String, data: &[u8], decoded: &[Decoded]) -> Vec<Field> {
+    let raw = hex_string(data);
+    let line = |text: String| Field {
+        text,
+        value: Some(raw.clone()),
+    };
+
+    let mut fields = vec![line(format!("  {}", source))];
+
+    if decoded.is_empty() {
+        fields.extend(
+            hex_dump(data)
+                .into_iter()
+                .map(|l| line(format!("    {}", l))),
+        );
+    } else {
+        for d in decoded {
+            fields.push(line(format!("    {}", d.format)));
+            fields.extend(
+                d.fields
+                    .iter()
+                    .map(|(name, value)| Field::labelled(name, value).indented(3)),
+            );
+        }
+    }
+
+    fields
 }
 
 pub fn draw_inspect_panel(
@@ -109,22 +234,193 @@ pub fn draw_inspect_panel(
     ui_state: &mut UIState,
     device: &BTDevice,
 ) {
-    if let Some(p) = &ui_state.inspect_text {
-        let b = Block::new()
-            .padding(Padding {
-                left: 4,
-                right: 4,
-                top: 1,
-                bottom: 1,
-            })
-            .title(format!(" Device: {} ", device.name))
-            .title_style(Style::new().bold().white())
-            .borders(Borders::ALL)
-            .border_style(Style::new().fg(BMColors::DARK_GRAY))
-            .border_type(BorderType::Rounded);
-
-        let paragraph = p.clone().block(b).wrap(Wrap { trim: true });
-
-        f.render_widget(paragraph, area);
+    let histories = ui_state.histories.clone();
+    let histories = histories.as_ref().borrow();
+
+    if let Some(view) = &mut ui_state.inspect {
+        let padding = Padding {
+            left: 4,
+            right: 4,
+            top: 1,
+            bottom: 1,
+        };
+
+        view.page = draw_device_details(
+            f,
+            area,
+            histories.get(&device.address),
+            device,
+            &view.fields,
+            &mut view.state,
+            padding,
+        );
+    }
+}
+
+/// The details of `device` in `fields`, with its history charted below them. Returns how many
+/// rows of fields fit.
+pub fn draw_device_details(
+    f: &mut Frame<'_>,
+    area: Rect,
+    history: Option<&DeviceHistory>,
+    device: &BTDevice,
+    fields: &[Field],
+    state: &mut ListState,
+    padding: Padding,
+) -> u16 {
+    let b = Block::new()
+        .padding(padding)
+        .title(format!(" Device: {} ", device.name))
+        .title_style(Style::new().bold().fg(colors().text))
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(colors().dark_gray))
+        .border_set(icons().border);
+
+    let fields_area = match history {
+        Some(history) if !history.rssi.is_empty() || !history.battery.is_empty() => {
+            let layout = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(vec![Constraint::Min(0), Constraint::Length(14)])
+                .split(area);
+
+            draw_history_chart(f, layout[1], history);
+            layout[0]
+        }
+        _ => area,
+    };
+
+    let inner = b.inner(fields_area);
+    let list = List::new(
+        fields
+            .iter()
+            .map(|field| ListItem::new(wrap_text(&field.text, inner.width as usize))),
+    )
+    .block(b)
+    .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+    f.render_stateful_widget(list, fields_area, state);
+
+    inner.height
+}
+
+/// Break `text` into lines no wider than `width` between words, indenting the lines it
+/// continues onto.
+fn wrap_text(text: &str, width: usize) -> Text<'static> {
+    if text.chars().count() <= width {
+        return Text::raw(text.to_string());
+    }
+
+    let indent = text.len() - text.trim_start().len();
+    let continued = " ".repeat((indent + 2).min(width / 2));
+
+    let mut lines = vec![];
+    let mut line = " ".repeat(indent);
+
+    for word in text.split_whitespace() {
+        let started = !line.trim().is_empty();
+
+        if started && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(Line::raw(std::mem::replace(&mut line, continued.clone())));
+        } else if started {
+            line.push(' ');
+        }
+
+        line.push_str(word);
+    }
+
+    lines.push(Line::raw(line));
+    Text::from(lines)
+}
+
+/// Chart signal strength and battery level against how long ago they were seen.
+fn draw_history_chart(f: &mut Frame<'_>, area: Rect, history: &DeviceHistory) {
+    let now = Instant::now();
+    let points = |series: &Series| {
+        series
+            .samples()
+            .map(|(t, v)| (-now.duration_since(*t).as_secs_f64(), *v))
+            .collect::<Vec<(f64, f64)>>()
+    };
+
+    let rssi = points(&history.rssi);
+    // Battery is charted on the same axis, scaled from 0-100% onto the RSSI range
+    let battery = points(&history.battery)
+        .into_iter()
+        .map(|(t, b)| (t, RSSI_FLOOR + b / 100.0 * (RSSI_CEILING - RSSI_FLOOR)))
+        .collect::<Vec<(f64, f64)>>();
+
+    let oldest = rssi
+        .iter()
+        .chain(battery.iter())
+        .map(|(t, _)| *t)
+        .fold(-60.0, f64::min);
+
+    let datasets = vec![
+        Dataset::default()
+            .name("RSSI (dBm)")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::new().fg(colors().blue2))
+            .data(&rssi),
+        Dataset::default()
+            .name("Battery (%)")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::new().fg(colors().green))
+            .data(&battery),
+    ];
+
+    let axis_style = Style::new().fg(colors().dark_gray);
+
+    let chart = Chart::new(datasets)
+        .block(
+            Block::new()
+                .title(history_title(history))
+                .title_style(Style::new().fg(colors().gray).bold())
+                .borders(Borders::ALL)
+                .border_style(Style::new().fg(colors().dark_gray))
+                .border_set(icons().border),
+        )
+        .x_axis(
+            Axis::default()
+                .style(axis_style)
+                .bounds([oldest, 0.0])
+                .labels(vec![format_age(-oldest), "now".to_string()]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(axis_style)
+                .bounds([RSSI_FLOOR, RSSI_CEILING])
+                .labels(vec![
+                    format!("{} / 0%", RSSI_FLOOR),
+                    format!("{} / 100%", RSSI_CEILING),
+                ]),
+        );
+
+    f.render_widget(chart, area);
+}
+
+/// Chart title, with the battery drain rate when there's enough history to estimate it.
+fn history_title(history: &DeviceHistory) -> Line<'static> {
+    let mut spans = vec![Span::raw(" History ")];
+
+    if let Some(rate) = history.drain_rate() {
+        spans.push(Span::raw(format!(
+            "{} draining {:.1}%/h ",
+            icons().dot,
+            rate
+        )));
+        spans.extend(format_drain_spans(history));
+        spans.push(Span::raw(" "));
+    }
+
+    Line::from(spans)
+}
+
+fn format_age(secs: f64) -> String {
+    match secs as u64 {
+        s if s < 120 => format!("-{}s", s),
+        s if s < 2 * 60 * 60 => format!("-{}m", s / 60),
+        s => format!("-{}h", s / (60 * 60)),
     }
 }
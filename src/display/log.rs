@@ -0,0 +1,85 @@
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Padding, Paragraph},
+    Frame,
+};
+
+use crate::app::{format_entry, Severity};
+
+use super::{colors::colors, icons::icons, UIState};
+
+pub fn draw_log_panel(f: &mut Frame, area: Rect, ui_state: &mut UIState) {
+    let log = ui_state.event_log.as_ref().borrow();
+    let view = &ui_state.log_view;
+
+    let entries = log
+        .entries()
+        .filter(|e| e.matches(view.min_severity, view.device.as_deref()))
+        .collect::<Vec<_>>();
+
+    let device_filter = match &view.device {
+        Some(addr) => log
+            .devices()
+            .into_iter()
+            .find(|(a, _)| a == addr)
+            .map_or(addr.clone(), |(_, name)| name),
+        None => "all devices".to_string(),
+    };
+
+    let block = Block::new()
+        .title(format!(
+            " Event log {} {} {} {} and above ({}) ",
+            icons().dot,
+            device_filter,
+            icons().dot,
+            view.min_severity.name(),
+            entries.len()
+        ))
+        .title_style(Style::new().fg(colors().gray).bold())
+        .padding(Padding {
+            left: 1,
+            right: 1,
+            top: 0,
+            bottom: 0,
+        })
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(colors().dark_gray))
+        .border_set(icons().border);
+
+    let height = block.inner(area).height as usize;
+
+    // Newest entries sit at the bottom, scrolling moves back through older ones
+    let max_scroll = entries.len().saturating_sub(height);
+    let scroll = view.scroll.min(max_scroll);
+    ui_state.log_view.scroll = scroll;
+
+    let end = entries.len() - scroll;
+    let start = end.saturating_sub(height);
+
+    let lines = entries[start..end]
+        .iter()
+        .map(|e| {
+            Line::from(Span::styled(
+                format_entry(e, false),
+                severity_style(e.severity),
+            ))
+        })
+        .collect::<Vec<Line>>();
+
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+fn severity_style(severity: Severity) -> Style {
+    match severity {
+        Severity::Info => Style::new().fg(colors().gray),
+        Severity::Success => Style::new().fg(colors().green),
+        Severity::Warning => colors().highlight(Style::new(), colors().orange, Modifier::BOLD),
+        Severity::Error => colors().highlight(
+            Style::new(),
+            colors().red,
+            Modifier::BOLD | Modifier::REVERSED,
+        ),
+    }
+}
@@ -1,11 +1,21 @@
 mod banner;
+mod beacons;
 mod colors;
+mod command;
 mod controls;
+mod details;
+mod finder;
 mod header;
+mod help;
 mod icons;
 mod inspect;
+mod log;
 mod table;
 mod ui;
 
-pub use inspect::format_inspect_text;
-pub use ui::{draw_ui, init_ui, shutdown_ui, UIState};
+pub use colors::{init_colors, ColorDepth, Theme};
+pub use details::DetailsPane;
+pub use icons::{init_icons, IconSet};
+pub use inspect::{format_inspect_fields, InspectView};
+pub use table::Column;
+pub use ui::{draw_ui, init_ui, shutdown_ui, HitAreas, UIState};
@@ -1,129 +1,580 @@
 use ratatui::{
     layout::{Constraint, Rect},
     style::{Modifier, Style, Stylize},
-    text::Span,
-    widgets::{Block, BorderType, Borders, Padding, Row, Table},
+    text::{Line, Span},
+    widgets::{Block, Borders, Padding, Row, Table},
     Frame,
 };
 
-use crate::app::BTDevice;
+use serde::Deserialize;
+use std::time::Duration;
 
-use super::{colors::BMColors, icons::get_icon_for_bt_type, UIState};
+use crate::app::{BTDevice, DeviceFilter, DeviceHistory, Series, RSSI_CEILING, RSSI_FLOOR};
+
+use super::{
+    colors::colors,
+    icons::{get_icon_for_device, icons},
+    UIState,
+};
+
+/// Number of recent RSSI samples drawn next to the signal icon.
+const SPARKLINE_WIDTH: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Column {
+    /// Connected or paired indicator.
+    Status,
+    Icon,
+    Name,
+    Alias,
+    Paired,
+    Connected,
+    Signal,
+    /// Signal strength in dBm.
+    Rssi,
+    Battery,
+    Address,
+    Vendor,
+    Transport,
+    LastSeen,
+}
+
+impl Column {
+    pub const DEFAULT: [Column; 8] = [
+        Column::Status,
+        Column::Icon,
+        Column::Name,
+        Column::Paired,
+        Column::Connected,
+        Column::Signal,
+        Column::Battery,
+        Column::Address,
+    ];
+
+    fn header(&self, compact: bool) -> &'static str {
+        match (self, compact) {
+            (Column::Status | Column::Icon, _) => " ",
+            (Column::Name, _) => "Name",
+            (Column::Alias, _) => "Alias",
+            (Column::Paired, false) => "Paired",
+            (Column::Paired, true) => "Pair",
+            (Column::Connected, false) => "Connected",
+            (Column::Connected, true) => "Conn",
+            (Column::Signal, false) => "Signal",
+            (Column::Signal, true) => "Sig",
+            (Column::Rssi, _) => "RSSI",
+            (Column::Battery, false) => "Battery",
+            (Column::Battery, true) => "Batt",
+            (Column::Address, _) => "Address",
+            (Column::Vendor, _) => "Vendor",
+            (Column::Transport, false) => "Transport",
+            (Column::Transport, true) => "Via",
+            (Column::LastSeen, false) => "Last seen",
+            (Column::LastSeen, true) => "Seen",
+        }
+    }
+
+    /// Width of the column, or the least the name gets since it takes up whatever's left.
+    fn width(&self, compact: bool) -> u16 {
+        match (self, compact) {
+            (Column::Status, _) => 1,
+            (Column::Icon, _) => 2,
+            (Column::Name, false) => 20,
+            (Column::Name, true) => 12,
+            (Column::Alias | Column::Vendor, false) => 16,
+            (Column::Alias | Column::Vendor, true) => 10,
+            (Column::Paired, false) => 6,
+            (Column::Connected, false) => 9,
+            (Column::Paired | Column::Connected, true) => 4,
+            (Column::Signal, false) => SPARKLINE_WIDTH as u16 + 3,
+            (Column::Signal, true) => 3,
+            (Column::Rssi, false) => 7,
+            (Column::Rssi, true) => 4,
+            (Column::Battery, false) => 32,
+            (Column::Battery, true) => 4,
+            (Column::Address, false) => 36,
+            (Column::Address, true) => 17,
+            (Column::Transport, false) => 9,
+            (Column::Transport, true) => 6,
+            (Column::LastSeen, false) => 9,
+            (Column::LastSeen, true) => 4,
+        }
+    }
+
+    /// Lower priorities are kept longest as the terminal narrows.
+    fn priority(&self) -> u8 {
+        match self {
+            Column::Name => 0,
+            Column::Status => 1,
+            Column::Battery => 2,
+            Column::Signal => 3,
+            Column::Icon => 4,
+            Column::Connected => 5,
+            Column::Alias => 6,
+            Column::Rssi => 7,
+            Column::Paired => 8,
+            Column::Vendor => 9,
+            Column::LastSeen => 10,
+            Column::Transport => 11,
+            Column::Address => 12,
+        }
+    }
+
+    fn constraint(&self, compact: bool) -> Constraint {
+        match self {
+            Column::Name => Constraint::Min(self.width(compact)),
+            _ => Constraint::Length(self.width(compact)),
+        }
+    }
+
+    fn cell(
+        &self,
+        d: &BTDevice,
+        history: Option<&DeviceHistory>,
+        s: Style,
+        compact: bool,
+    ) -> Line<'static> {
+        match self {
+            Column::Status => Line::from(if d.connected {
+                Span::styled(icons().connected, s)
+            } else if d.paired {
+                Span::styled(icons().paired, s)
+            } else {
+                Span::raw(" ")
+            }),
+            Column::Icon => Span::styled(get_icon_for_device(d).to_owned() + " ", s).into(),
+            Column::Name => Span::styled(d.name.to_owned(), s).into(),
+            Column::Alias => Span::styled(d.alias.to_owned(), s).into(),
+            Column::Paired => {
+                if d.paired {
+                    Span::styled("Yes", s.fg(colors().green)).into()
+                } else {
+                    Line::default()
+                }
+            }
+            Column::Connected => {
+                if d.connected {
+                    Span::styled("Yes", s.fg(colors().green)).into()
+                } else {
+                    Line::default()
+                }
+            }
+            Column::Signal if compact => format_signal_span(d.rssi).into(),
+            Column::Signal => Line::from(vec![
+                format_signal_span(d.rssi),
+                Span::styled(
+                    history
+                        .map(|h| format_sparkline(&h.rssi, SPARKLINE_WIDTH))
+                        .unwrap_or_default(),
+                    Style::new().fg(colors().blue2),
+                ),
+            ]),
+            Column::Rssi => match d.rssi {
+                Some(rssi) if compact => Span::styled(rssi.to_string(), s).into(),
+                Some(rssi) => Span::styled(format!("{} dBm", rssi), s).into(),
+                None => Line::default(),
+            },
+            // Only the lowest level fits, which is the one that matters for AirPods
+            Column::Battery if compact => match d.battery_level() {
+                Some(level) => Span::styled(format!("{}%", level), battery_style(level)).into(),
+                None => Line::default(),
+            },
+            Column::Battery => {
+                let mut battery = format_battery_line(d);
+                if let Some(history) = history {
+                    battery.spans.extend(format_drain_spans(history));
+                }
+                battery
+            }
+            Column::Address if compact => {
+                Span::styled(d.address.clone(), s.fg(colors().gray)).into()
+            }
+            Column::Address => Span::styled(
+                format!("{} {}", d.address, d.address_kind),
+                s.fg(colors().gray),
+            )
+            .into(),
+            Column::Vendor => Span::styled(d.vendor().unwrap_or_default(), s).into(),
+            Column::Transport => Span::styled(d.transport().unwrap_or_default(), s).into(),
+            Column::LastSeen => history
+                .map(|h| {
+                    Span::raw(format_ago(h.last_seen.elapsed()))
+                        .fg(colors().gray)
+                        .into()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The columns that fit in `width`, and whether each is abbreviated. When they don't all fit,
+/// the least important column is abbreviated, then the next, and once they all are the least
+/// important are dropped until the rest fit.
+fn fit_columns(columns: &[Column], width: u16) -> Vec<(Column, bool)> {
+    let needed = |columns: &[(Column, bool)]| {
+        columns
+            .iter()
+            .map(|(c, compact)| c.width(*compact) + 1)
+            .sum::<u16>()
+            .saturating_sub(1)
+    };
+
+    let mut fitted = columns.iter().map(|c| (*c, false)).collect::<Vec<_>>();
+
+    let mut by_priority = (0..fitted.len()).collect::<Vec<usize>>();
+    by_priority.sort_by_key(|&i| std::cmp::Reverse(fitted[i].0.priority()));
+
+    for i in by_priority {
+        if needed(&fitted) <= width {
+            return fitted;
+        }
+        fitted[i].1 = true;
+    }
+
+    while fitted.len() > 1 && needed(&fitted) > width {
+        let (idx, _) = fitted
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, (c, _))| c.priority())
+            .unwrap();
+        fitted.remove(idx);
+    }
+
+    fitted
+}
 
 pub fn draw_table(f: &mut Frame, area: Rect, ui_state: &mut UIState) {
     let d = ui_state.devices.as_ref().borrow(); // Thank u borrow checker :pray:
+    let histories = ui_state.histories.as_ref().borrow();
 
-    let table_header_style = Style::new().bold().dark_gray();
+    // Leave out the borders and padding
+    let columns = fit_columns(&ui_state.columns, area.width.saturating_sub(4));
+
+    let table_header_style = Style::new().bold().fg(colors().gray);
 
     // Define table rows
-    let mut rows = vec![Row::new(
-        vec![
-            " ",
-            " ",
-            "Name",
-            "Paired",
-            "Connected",
-            "Signal",
-            "Battery",
-            "Address",
-        ]
-        .iter()
-        .map(|t| Span::styled(*t, table_header_style)),
-    )];
+    let mut rows =
+        vec![Row::new(columns.iter().map(|(c, compact)| {
+            Span::styled(c.header(*compact), table_header_style)
+        }))];
 
     rows.extend(
         d.iter()
-            .filter(|d| (d.name != "???") || ui_state.show_unnamed)
+            .filter(|d| ui_state.is_listed(d))
             .map(|d: &BTDevice| {
                 let s = match d.connected {
-                    true => Style::new().fg(BMColors::BLUE), // Style::new().on_blue(),
+                    true => colors().highlight(Style::new(), colors().blue, Modifier::BOLD),
                     false => Style::new(),
                 };
 
-                Row::new(vec![
-                    if d.connected {
-                        Span::styled("󰂱", s)
-                    } else if d.paired {
-                        Span::styled("󰂯", s)
-                    } else {
-                        Span::raw(" ")
-                    },
-                    Span::styled(get_icon_for_bt_type(&d.icon_name).to_owned() + " ", s),
-                    Span::styled(d.name.to_owned(), s),
-                    if d.paired {
-                        Span::styled("Yes", s.green())
-                    } else {
-                        Span::default()
-                    },
-                    if d.connected {
-                        Span::styled("Yes", s.green())
-                    } else {
-                        Span::default()
-                    },
-                    format_signal_span(d.rssi),
-                    format_battery_span(d.battery),
-                    Span::styled(d.address.to_owned(), s.dark_gray()),
-                ])
+                let history = histories.get(&d.address);
+
+                Row::new(
+                    columns
+                        .iter()
+                        .map(|(c, compact)| c.cell(d, history, s, *compact)),
+                )
             }),
     );
 
+    let block = Block::new()
+        .title(format_title(&ui_state.filters))
+        .title_style(Style::new().fg(colors().gray).bold())
+        .padding(Padding {
+            left: 1,
+            right: 1,
+            top: 0,
+            bottom: 0,
+        })
+        .borders(Borders::ALL)
+        .border_style(Style::new().fg(colors().dark_gray))
+        .border_set(icons().border);
+
+    let rows_area = block.inner(area);
+
     // Define the table
     let table = Table::new(
         rows,
-        vec![
-            Constraint::Length(1),
-            Constraint::Length(2),
-            Constraint::Percentage(99),
-            Constraint::Length(10),
-            Constraint::Length(10),
-            Constraint::Length(10),
-            Constraint::Length(18),
-            Constraint::Length(20),
-        ],
+        columns.iter().map(|(c, compact)| c.constraint(*compact)),
     )
     .column_spacing(1)
-    .block(
-        Block::new()
-            .title(" Devices ")
-            .title_style(Style::new().fg(BMColors::GRAY).bold())
-            .padding(Padding {
-                left: 1,
-                right: 1,
-                top: 0,
-                bottom: 0,
-            })
-            .borders(Borders::ALL)
-            .border_style(Style::new().fg(BMColors::DARK_GRAY))
-            .border_type(BorderType::Rounded),
-    )
+    .block(block)
     .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
 
+    drop(d);
+    drop(histories);
+
     f.render_stateful_widget(table, area, &mut ui_state.table_state);
+    ui_state.hit_areas.table = rows_area;
+}
+
+/// The table title, with any filters from the command line.
+fn format_title(filters: &[DeviceFilter]) -> String {
+    if filters.is_empty() {
+        " Devices ".to_string()
+    } else {
+        let filters = filters
+            .iter()
+            .map(|f| f.to_string())
+            .collect::<Vec<String>>();
+        format!(" Devices {} {} ", icons().dot, filters.join(" "))
+    }
+}
+
+/// The battery level's colour, or bold and then reversed as it runs low when colours are off.
+fn battery_style(level: u8) -> Style {
+    let color = match level {
+        0..10 => colors().red,
+        10..40 => colors().orange,
+        40..70 => colors().yellow,
+        70.. => colors().green,
+    };
+
+    let fallback = match level {
+        0..10 => Modifier::BOLD | Modifier::REVERSED,
+        10..40 => Modifier::BOLD,
+        _ => Modifier::empty(),
+    };
+
+    colors().highlight(Style::new(), color, fallback)
 }
 
+/// A battery glyph, or the level itself with icon sets which have none.
 fn format_battery_span(battery: Option<u8>) -> Span<'static> {
     if let Some(b) = battery {
-        match b {
-            0..10 => Span::styled(" ", Style::new().fg(BMColors::RED)),
-            10..40 => Span::styled(" ", Style::new().fg(BMColors::ORANGE)),
-            40..60 => Span::styled(" ", Style::new().fg(BMColors::YELLOW)),
-            70..90 => Span::styled(" ", Style::new().fg(BMColors::GREEN)),
-            90.. => Span::styled(" ", Style::new().fg(BMColors::GREEN)),
-            _ => unreachable!(),
+        let style = battery_style(b);
+
+        match icons().battery(b) {
+            Some(glyph) => Span::styled(glyph, style),
+            None => Span::styled(format!("{}% ", b), style),
         }
     } else {
         Span::raw("")
     }
 }
 
-fn format_signal_span(battery: Option<i16>) -> Span<'static> {
-    if let Some(b) = battery {
-        match b {
-            -50..0 => Span::styled("󰢾 ", Style::new().fg(BMColors::GREEN)),
-            -90..-50 => Span::styled("󰢽 ", Style::new().fg(BMColors::YELLOW)),
-            _ => Span::styled("󰢼 ", Style::new().fg(BMColors::RED)),
-        }
+/// Format the battery column, showing each component of devices which report more than one
+/// battery, such as AirPods.
+fn format_battery_line(device: &BTDevice) -> Line<'static> {
+    match &device.apple_accessory {
+        Some(accessory) if device.battery.is_none() => Line::from(
+            accessory
+                .levels()
+                .into_iter()
+                .flat_map(|(label, level)| match icons().battery(level) {
+                    Some(_) => vec![
+                        Span::raw(label),
+                        format_battery_span(Some(level)),
+                        Span::raw(format!("{}% ", level)).fg(colors().gray),
+                    ],
+                    None => vec![
+                        Span::raw(format!("{} ", label)),
+                        format_battery_span(Some(level)),
+                    ],
+                })
+                .collect::<Vec<Span>>(),
+        ),
+        _ => format_battery_span(device.battery).into(),
+    }
+}
+
+/// Estimated time left, with a warning when the battery has started draining faster.
+pub fn format_drain_spans(history: &DeviceHistory) -> Vec<Span<'static>> {
+    let mut spans = vec![];
+
+    if let Some(left) = history.time_left() {
+        spans.push(
+            Span::raw(format!("{}{} left ", icons().about, format_duration(left)))
+                .fg(colors().gray),
+        );
+    }
+
+    if history.drain_speeding_up() {
+        spans.push(Span::styled(
+            format!("{} draining fast", icons().warning),
+            colors().highlight(Style::new(), colors().orange, Modifier::BOLD),
+        ));
+    }
+
+    spans
+}
+
+/// How long ago something happened, e.g. "now", "12s" or "5m".
+fn format_ago(d: Duration) -> String {
+    match d.as_secs() {
+        0..5 => "now".to_string(),
+        s @ 5..60 => format!("{}s", s),
+        _ => format_duration(d),
+    }
+}
+
+/// Round a duration to its largest unit, e.g. "45m", "3h" or "2d".
+fn format_duration(d: Duration) -> String {
+    match d.as_secs() {
+        s if s < 60 * 60 => format!("{}m", s.div_ceil(60)),
+        s if s < 48 * 60 * 60 => format!("{}h", (s as f64 / (60.0 * 60.0)).round()),
+        s => format!("{}d", (s as f64 / (24.0 * 60.0 * 60.0)).round()),
+    }
+}
+
+fn format_signal_span(rssi: Option<i16>) -> Span<'static> {
+    if let Some(r) = rssi {
+        let color = match r {
+            -50..0 => colors().green,
+            -90..-50 => colors().yellow,
+            _ => colors().red,
+        };
+
+        Span::styled(icons().signal(r), Style::new().fg(color))
     } else {
         Span::raw("")
     }
 }
+
+/// Draw the last `width` samples of an RSSI series with block characters.
+fn format_sparkline(series: &Series, width: usize) -> String {
+    let bars = icons().bars;
+
+    let skip = series.samples().len().saturating_sub(width);
+
+    series
+        .samples()
+        .skip(skip)
+        .map(|(_, rssi)| {
+            let level =
+                (rssi.clamp(RSSI_FLOOR, RSSI_CEILING) - RSSI_FLOOR) / (RSSI_CEILING - RSSI_FLOOR);
+            bars[(level * (bars.len() - 1) as f64).round() as usize]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The columns fitted to `width`, with abbreviated ones in lower case.
+    fn fit(width: u16) -> Vec<String> {
+        fit_columns(&Column::DEFAULT, width)
+            .into_iter()
+            .map(|(c, compact)| {
+                let name = format!("{:?}", c);
+                if compact {
+                    name.to_lowercase()
+                } else {
+                    name
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn everything_fits() {
+        let full = [
+            "Status",
+            "Icon",
+            "Name",
+            "Paired",
+            "Connected",
+            "Signal",
+            "Battery",
+            "Address",
+        ];
+
+        assert_eq!(fit(200), full);
+        assert_eq!(fit(124), full);
+    }
+
+    #[test]
+    fn abbreviate_least_important_first() {
+        let address = [
+            "Status",
+            "Icon",
+            "Name",
+            "Paired",
+            "Connected",
+            "Signal",
+            "Battery",
+            "address",
+        ];
+        assert_eq!(fit(123), address);
+        assert_eq!(fit(105), address);
+
+        // The battery details last until everything less important is abbreviated
+        assert_eq!(
+            fit(100),
+            [
+                "Status",
+                "Icon",
+                "Name",
+                "paired",
+                "connected",
+                "Signal",
+                "Battery",
+                "address"
+            ]
+        );
+        assert_eq!(
+            fit(90),
+            [
+                "Status",
+                "icon",
+                "Name",
+                "paired",
+                "connected",
+                "signal",
+                "Battery",
+                "address"
+            ]
+        );
+        assert_eq!(
+            fit(70),
+            [
+                "Status",
+                "icon",
+                "Name",
+                "paired",
+                "connected",
+                "signal",
+                "battery",
+                "address"
+            ]
+        );
+    }
+
+    #[test]
+    fn drop_least_important_last() {
+        assert_eq!(
+            fit(54),
+            [
+                "status",
+                "icon",
+                "name",
+                "paired",
+                "connected",
+                "signal",
+                "battery",
+                "address"
+            ]
+        );
+        assert_eq!(
+            fit(40),
+            [
+                "status",
+                "icon",
+                "name",
+                "paired",
+                "connected",
+                "signal",
+                "battery"
+            ]
+        );
+        assert_eq!(fit(20), ["status", "name", "battery"]);
+        assert_eq!(fit(5), ["name"]);
+    }
+
+    #[test]
+    fn no_columns() {
+        assert!(fit_columns(&[], 80).is_empty());
+    }
+}
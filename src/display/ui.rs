@@ -1,11 +1,14 @@
 use std::{
     cell::RefCell,
+    collections::HashMap,
     io::{stdout, Stdout},
     rc::Rc,
+    time::Instant,
 };
 
 use anyhow::Result;
 use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
@@ -17,46 +20,125 @@ use ratatui::{
         Direction::{self},
         Layout, Rect,
     },
-    style::{Style, Stylize},
+    style::Style,
     text::Span,
-    widgets::{Block, BorderType, Borders, Paragraph, TableState},
+    widgets::{Block, Borders, Paragraph, TableState},
     Frame, Terminal,
 };
 
-use crate::app::{AppMode, BTDevice, Banner};
+use crate::app::{
+    Action, AppMode, BTDevice, Banner, BeaconTracker, CommandLine, DeviceFilter, DeviceHistory,
+    EventLog, Finder, Keymap, LogView,
+};
 
 use super::{
     banner::draw_banner,
-    colors::BMColors,
-    controls::{draw_browse_controls, draw_inspect_controls, draw_quit_hint},
+    beacons::draw_beacon_table,
+    colors::colors,
+    command::draw_command_line,
+    controls::{
+        draw_beacon_controls, draw_browse_controls, draw_find_controls, draw_inspect_controls,
+        draw_log_controls, draw_quit_hint,
+    },
+    details::{draw_details_pane, DetailsPane},
+    finder::draw_find_panel,
     header::draw_header,
-    inspect::draw_inspect_panel,
-    table::draw_table,
+    help::draw_help_overlay,
+    icons::icons,
+    inspect::{draw_inspect_panel, Field, InspectView},
+    log::draw_log_panel,
+    table::{draw_table, Column},
 };
 
-pub struct UIState<'a> {
+pub struct UIState {
     pub devices: Rc<RefCell<Vec<BTDevice>>>,
+    pub beacons: Rc<RefCell<BeaconTracker>>,
+    pub histories: Rc<RefCell<HashMap<String, DeviceHistory>>>,
+    pub event_log: Rc<RefCell<EventLog>>,
+    pub log_view: LogView,
+    /// Rows scrolled past at the top of the beacon table.
+    pub beacon_scroll: usize,
     pub banner: Option<Banner>,
     pub table_state: TableState,
     pub show_unnamed: bool,
-    pub inspect_text: Option<Paragraph<'a>>,
+    pub columns: Vec<Column>,
+    pub keymap: Keymap,
+    pub inspect: Option<InspectView>,
+    pub details: DetailsPane,
+    /// Details of the highlighted device for the pane, with the address they're for.
+    pub details_fields: Option<(String, Vec<Field>)>,
+    pub finder: Option<Finder>,
+    /// Scroll position of the help overlay, while it's open.
+    pub help: Option<u16>,
+    pub command: Option<CommandLine>,
+    /// Only list devices matching all of these.
+    pub filters: Vec<DeviceFilter>,
+    pub hit_areas: HitAreas,
+    /// When a device was last clicked, to tell a double click.
+    pub last_click: Option<(Instant, String)>,
+}
+
+/// Where things were last drawn, so clicks can be matched to them.
+#[derive(Debug, Clone, Default)]
+pub struct HitAreas {
+    /// Rows of the device table, starting with its header.
+    pub table: Rect,
+    /// Controls along the bottom, with the action each runs.
+    pub controls: Vec<(Rect, Action)>,
+}
+
+impl UIState {
+    /// Whether the device table shows `device`.
+    pub fn is_listed(&self, device: &BTDevice) -> bool {
+        ((device.name != "???") || self.show_unnamed)
+            && self.filters.iter().all(|f| f.matches(device))
+    }
+
+    /// The devices in the table, in order.
+    pub fn listed_devices(&self) -> Vec<BTDevice> {
+        self.devices
+            .as_ref()
+            .borrow()
+            .iter()
+            .filter(|d| self.is_listed(d))
+            .cloned()
+            .collect()
+    }
+
+    /// The device highlighted in the table.
+    pub fn selected_device(&self) -> Option<BTDevice> {
+        let row = self.table_state.selected()?;
+        self.listed_devices().into_iter().nth(row.checked_sub(1)?)
+    }
+
+    /// Highlight `device` in the table, if it's listed.
+    pub fn select_device(&mut self, device: &BTDevice) {
+        if let Some(idx) = self.listed_devices().iter().position(|d| d == device) {
+            // The header is the first row
+            *self.table_state.selected_mut() = Some(idx + 1);
+        }
+    }
 }
 
 /// Setup the necessary components to make terminal ui calls.
-pub fn init_ui() -> Result<Terminal<CrosstermBackend<Stdout>>> {
+pub fn init_ui(mouse: bool) -> Result<Terminal<CrosstermBackend<Stdout>>> {
     enable_raw_mode()?;
     stdout().execute(EnterAlternateScreen)?;
+    if mouse {
+        stdout().execute(EnableMouseCapture)?;
+    }
     Ok(Terminal::new(CrosstermBackend::new(stdout()))?)
 }
 
 /// Teardown ui components, and release the terminal back to the user.
 pub fn shutdown_ui() -> Result<()> {
     disable_raw_mode()?;
+    stdout().execute(DisableMouseCapture)?;
     stdout().execute(LeaveAlternateScreen)?;
     Ok(())
 }
 
-pub fn draw_ui(f: &mut Frame<'_>, ui_state: &mut UIState<'_>, mode: AppMode) {
+pub fn draw_ui(f: &mut Frame<'_>, ui_state: &mut UIState, mode: AppMode) {
     let layout = Layout::default()
         .direction(Direction::Vertical)
         .constraints(vec![
@@ -78,19 +160,45 @@ pub fn draw_ui(f: &mut Frame<'_>, ui_state: &mut UIState<'_>, mode: AppMode) {
     match &mode {
         AppMode::Inspect(d) => {
             draw_inspect_panel(f, table_area, ui_state, d);
-            draw_inspect_controls(f, controls_area);
+            draw_inspect_controls(f, controls_area, ui_state);
+        }
+        AppMode::Find => {
+            draw_find_panel(f, table_area, ui_state);
+            draw_find_controls(f, controls_area, ui_state);
+        }
+        AppMode::Log => {
+            draw_log_panel(f, table_area, ui_state);
+            draw_log_controls(f, controls_area, ui_state);
+        }
+        AppMode::Beacons => {
+            draw_beacon_table(f, table_area, ui_state);
+            draw_beacon_controls(f, controls_area, ui_state);
         }
         _ => {
+            let (table_area, details_area) = ui_state.details.split(table_area);
+
             draw_table(f, table_area, ui_state);
-            draw_browse_controls(f, controls_area);
+            draw_browse_controls(f, controls_area, ui_state);
+
+            if let Some(area) = details_area {
+                draw_details_pane(f, area, ui_state);
+            }
         }
     }
 
-    match mode {
-        AppMode::TryConnect(d) => draw_try_connect_panel(f, d),
-        AppMode::TryDisconnect(d) => draw_try_disconnect_panel(f, d),
+    match &mode {
+        AppMode::TryConnect(d) => draw_try_connect_panel(f, d.clone()),
+        AppMode::TryDisconnect(d) => draw_try_disconnect_panel(f, d.clone()),
         _ => {}
     }
+
+    if ui_state.command.is_some() {
+        draw_command_line(f, controls_area, ui_state);
+    }
+
+    if ui_state.help.is_some() {
+        draw_help_overlay(f, ui_state, &mode);
+    }
 }
 
 fn draw_try_connect_panel(f: &mut Frame, d: BTDevice) {
@@ -108,10 +216,13 @@ fn draw_try_connect_panel(f: &mut Frame, d: BTDevice) {
     // Create a block with borders and a title.
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
-        .style(Style::default().fg(BMColors::BLUE));
+        .border_set(icons().border)
+        .style(Style::default().fg(colors().blue));
 
-    let span = Span::styled(format!(" Connecting to {} ", d.name), Style::new().white());
+    let span = Span::styled(
+        format!(" Connecting to {} ", d.name),
+        Style::new().fg(colors().text),
+    );
 
     let p = Paragraph::new(span).block(block);
 
@@ -134,12 +245,12 @@ fn draw_try_disconnect_panel(f: &mut Frame, d: BTDevice) {
     // Create a block with borders and a title.
     let block = Block::default()
         .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
-        .style(Style::default().fg(BMColors::GRAY));
+        .border_set(icons().border)
+        .style(Style::default().fg(colors().gray));
 
     let span = Span::styled(
         format!(" Disconnecting from {} ", d.name),
-        Style::new().white(),
+        Style::new().fg(colors().text),
     );
 
     let p = Paragraph::new(span).block(block);
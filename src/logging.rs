@@ -0,0 +1,188 @@
+//! Log to a file, since the TUI owns the terminal, and write events for scripts to
+//! `--events=<path>`.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
+
+use anyhow::{anyhow, Result};
+use env_logger::{Builder, Env, Target, WriteStyle};
+
+use crate::{
+    config::{LogConfig, LogLevel},
+    xdg::state_dir,
+};
+
+/// Size a log file can grow to before it's rotated.
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+
+/// Number of rotated files kept alongside the current one.
+const KEEP_LOGS: usize = 3;
+
+static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+static EVENTS: OnceLock<Mutex<EventSink>> = OnceLock::new();
+
+/// Start logging to the configured file, or `bluetool.log` in the XDG state directory.
+///
+/// The level comes from the config, falling back to `RUST_LOG` and then `info`.
+pub fn init_logging(config: &LogConfig) -> Result<()> {
+    let path = match config.file.clone() {
+        Some(path) => path,
+        None => state_dir()
+            .ok_or_else(|| anyhow!("Couldn't find a state directory for the log file"))?
+            .join("bluetool.log"),
+    };
+
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        fs::create_dir_all(dir).map_err(|e| anyhow!("Couldn't create {}: {}", dir.display(), e))?;
+    }
+
+    let file = RotatingFile::open(path.clone())
+        .map_err(|e| anyhow!("Couldn't open log file {}: {}", path.display(), e))?;
+
+    let mut builder = Builder::from_env(Env::default().default_filter_or("info"));
+
+    if let Some(LogLevel(level)) = config.level {
+        builder.filter_level(level);
+    }
+
+    builder
+        .target(Target::Pipe(Box::new(file)))
+        .write_style(WriteStyle::Never)
+        .try_init()?;
+
+    let _ = LOG_PATH.set(path);
+
+    Ok(())
+}
+
+/// Path of the file being logged to.
+pub fn log_path() -> Option<&'static Path> {
+    LOG_PATH.get().map(PathBuf::as_path)
+}
+
+/// Start writing events to `path`, which may be a file, a FIFO or `/dev/fd/<n>`.
+pub fn init_events(path: &Path) -> Result<()> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| anyhow!("Couldn't open events file {}: {}", path.display(), e))?;
+
+    let _ = EVENTS.set(Mutex::new(EventSink(Box::new(file))));
+
+    Ok(())
+}
+
+/// Write an event, already serialized as JSON, if `--events` was given.
+pub fn write_event(json: &str) {
+    let Some(sink) = EVENTS.get() else {
+        return;
+    };
+
+    if let Err(e) = sink.lock().unwrap().write(json) {
+        log::warn!("Couldn't write event: {}", e);
+    }
+}
+
+/// Writes each event as one line of bare JSON, so scripts can read it with no parsing
+/// beyond splitting lines.
+struct EventSink(Box<dyn Write + Send>);
+
+impl EventSink {
+    fn write(&mut self, json: &str) -> io::Result<()> {
+        writeln!(self.0, "{}", json)?;
+        self.0.flush()
+    }
+}
+
+/// Appends to a file, moving it aside to `<path>.1`, `<path>.2`, ... once it gets too big.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(RotatingFile { path, file, size })
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let rotated = |n: usize| {
+            let mut name = self.path.clone().into_os_string();
+            name.push(format!(".{}", n));
+            PathBuf::from(name)
+        };
+
+        for n in (1..KEEP_LOGS).rev() {
+            let from = rotated(n);
+            if from.exists() {
+                fs::rename(&from, rotated(n + 1))?;
+            }
+        }
+
+        fs::rename(&self.path, rotated(1))?;
+        *self = RotatingFile::open(self.path.clone())?;
+
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.size + buf.len() as u64 > MAX_LOG_SIZE && self.size > 0 {
+            self.rotate()?;
+        }
+
+        let n = self.file.write(buf)?;
+        self.size += n as u64;
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn events_are_bare_json_lines() {
+        let out = Shared::default();
+        let mut sink = EventSink(Box::new(out.clone()));
+
+        sink.write(r#"{"event":"a"}"#).unwrap();
+        sink.write(r#"{"event":"b"}"#).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out.0.lock().unwrap().clone()).unwrap(),
+            "{\"event\":\"a\"}\n{\"event\":\"b\"}\n"
+        );
+    }
+}
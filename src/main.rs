@@ -1,8 +1,18 @@
 mod app;
+mod config;
+mod decode;
 mod display;
+mod logging;
 mod panic;
+mod xdg;
 
-use crate::panic::initialize_panic_handler;
+use crate::{
+    config::Config,
+    decode::load_custom_uuid_names,
+    display::{init_colors, init_icons},
+    logging::{init_events, init_logging},
+    panic::initialize_panic_handler,
+};
 use anyhow::Result;
 use app::BluetoolApp;
 
@@ -10,7 +20,26 @@ use app::BluetoolApp;
 async fn main() -> Result<()> {
     initialize_panic_handler();
 
-    let mut app = BluetoolApp::new();
+    let config = Config::load()?;
+
+    // The log is only there to diagnose problems, carry on without it
+    if let Err(e) = init_logging(&config.log) {
+        eprintln!("Warning: not logging to a file: {:#}", e);
+    }
+    log::info!("Starting bluetool {}", env!("CARGO_PKG_VERSION"));
+
+    // Unlike the log, events were asked for, so a script shouldn't be left waiting on them
+    if let Some(path) = &config.log.events {
+        init_events(path)?;
+    }
+
+    if let Err(e) = load_custom_uuid_names() {
+        log::warn!("Not loading custom UUID names: {:#}", e);
+    }
+    init_colors(config.theme, config.color);
+    init_icons(config.icons);
+
+    let mut app = BluetoolApp::new(config);
 
     app.run().await.unwrap();
 
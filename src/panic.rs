@@ -2,6 +2,7 @@ use better_panic::Settings;
 
 pub fn initialize_panic_handler() {
     std::panic::set_hook(Box::new(|panic_info| {
+        log::error!("{}", panic_info);
         crossterm::execute!(std::io::stderr(), crossterm::terminal::LeaveAlternateScreen).unwrap();
         crossterm::terminal::disable_raw_mode().unwrap();
         Settings::auto()
@@ -0,0 +1,22 @@
+use std::{env, path::PathBuf};
+
+/// Directory holding bluetool's user configuration files.
+///
+/// Resolves to `$XDG_CONFIG_HOME/bluetool`, falling back to `~/.config/bluetool`.
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CONFIG_HOME", ".config").map(|d| d.join("bluetool"))
+}
+
+/// Directory for state which should outlive a run, such as logs.
+///
+/// Resolves to `$XDG_STATE_HOME/bluetool`, falling back to `~/.local/state/bluetool`.
+pub fn state_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_STATE_HOME", ".local/state").map(|d| d.join("bluetool"))
+}
+
+fn xdg_dir(var: &str, home_fallback: &str) -> Option<PathBuf> {
+    match env::var_os(var) {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => env::var_os("HOME").map(|home| PathBuf::from(home).join(home_fallback)),
+    }
+}